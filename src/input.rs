@@ -2,13 +2,103 @@ use serde::{Deserialize, Serialize};
 use yew::prelude::*;
 use yew::services::storage::{Area, StorageService};
 
+mod data_file;
+mod dependencies;
 mod fn_input;
-pub use fn_input::{FnInput, FnInputKind};
+mod fourier;
+mod heatmap;
+mod parameter;
+mod phase_portrait;
+mod tangent;
+pub use data_file::{DataFile, SetDataFile};
+pub use dependencies::Dependencies;
+pub use fn_input::{
+    initial_value_problem, parse_definition, piecewise, points, slope_field, taylor, Binning,
+    ComplexExpression, Context, DisplayMode, FitModel, FitResult, FnInput, FnInputKind,
+    Interpolation, MarkerShape, SetDistribution, SetFit, SetIntegral, SetPointStyle, SetSolver,
+    SetTaylor, SolutionKey, MAX_DERIVATIVES, MAX_FIT_DEGREE, MAX_FOURIER_ORDER, MAX_TAYLOR_ORDER,
+};
+pub use fourier::{Fourier, SetFourier};
+pub use heatmap::{Colormap, Heatmap, SetHeatmap};
+pub use parameter::{Parameter, SetParameter};
+pub use phase_portrait::PhasePortrait;
+pub use tangent::{Linearization, SetTangent, Tangent};
+
+/// What is plotted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Mode {
+    /// The function inputs, in the plane.
+    #[default]
+    Cartesian,
+    /// The complex function, coloring each point by its value.
+    DomainColoring,
+    /// Surfaces and curves in space.
+    ThreeD,
+    /// A function of two variables, coloring each point by its value.
+    Heatmap,
+    /// Trajectories and fixed points of an autonomous system in the plane.
+    PhasePortrait,
+}
+
+impl Mode {
+    pub const ALL: [Mode; 5] = [
+        Mode::Cartesian,
+        Mode::DomainColoring,
+        Mode::ThreeD,
+        Mode::Heatmap,
+        Mode::PhasePortrait,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mode::Cartesian => "Cartesian",
+            Mode::DomainColoring => "Domain coloring",
+            Mode::ThreeD => "3D",
+            Mode::Heatmap => "Heatmap",
+            Mode::PhasePortrait => "Phase portrait",
+        }
+    }
+}
+
+/// Orientation and zoom of the 3D view, as understood by `plotters`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Projection {
+    pub yaw: f64,
+    pub pitch: f64,
+    pub scale: f64,
+}
+
+impl Default for Projection {
+    fn default() -> Self {
+        Projection {
+            yaw: 0.5,
+            pitch: 0.15,
+            scale: 0.8,
+        }
+    }
+}
+
+impl Projection {
+    /// Rotates the view as when dragging the mouse by `(dx, dy)` pixels.
+    pub fn rotate(&mut self, dx: i32, dy: i32) {
+        self.yaw += f64::from(dx) / 100.;
+        let limit = core::f64::consts::FRAC_PI_2;
+        self.pitch = (self.pitch + f64::from(dy) / 100.).max(-limit).min(limit);
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Input {
+    pub mode: Mode,
     pub canvas_size: (u32, u32),
     pub domain: (f64, f64),
+    /// Domain of `y` for surfaces in 3D mode, for the heatmap and for the phase portrait.
+    pub y_domain: (f64, f64),
+    /// Vertical range of the plot, or the range of the colors in heatmap mode,
+    /// used if `auto_range` is disabled.
+    pub range: (f64, f64),
+    pub auto_range: bool,
     pub mesh: bool,
     pub x_axis: bool,
     pub y_axis: bool,
@@ -16,9 +106,24 @@ pub struct Input {
     pub title_string: String,
     pub quality: usize,
     pub functions: Vec<FnInput>,
+    /// Free variables of the functions, sorted by name.
+    pub parameters: Vec<Parameter>,
+    /// Function plotted in domain coloring mode.
+    pub complex_function: ComplexExpression,
+    /// Whether domain coloring shows lines where the modulus is a power of two.
+    pub modulus_contours: bool,
+    pub projection: Projection,
+    pub heatmap: Heatmap,
+    /// Number of segments of slope fields and vector fields along each axis.
+    pub slope_field_density: usize,
+    pub phase_portrait: PhasePortrait,
+    /// Whether zeros, local extrema and intersections of the functions of `x` are marked.
+    pub key_points: bool,
+    pub tangent: Tangent,
+    pub fourier: Fourier,
 }
 
-const KEY: &'static str = "online_plotter.v.0.1.1.input";
+const KEY: &str = "online_plotter.v.0.1.1.input";
 impl Input {
     pub fn restore_or_default() -> Self {
         log::trace!("Restoring values from storage");
@@ -41,12 +146,116 @@ impl Input {
     }
 }
 
-impl<'a> Default for Input {
+impl Input {
+    pub fn dependencies(&self) -> Dependencies {
+        Dependencies::new(&self.functions)
+    }
+
+    /// Context in which the functions are evaluated: named functions that can be
+    /// evaluated are made callable, in an order compatible with their dependencies.
+    pub fn context(&self, dependencies: &Dependencies) -> Context<'_> {
+        let mut context = Context::default();
+        for &index in &dependencies.order {
+            let function = &self.functions[index];
+            if let Some(name) = function.name() {
+                context.insert_function(name, function.kind());
+            }
+        }
+        for parameter in &self.parameters {
+            context.insert_parameter(&parameter.name, parameter.value);
+        }
+        context
+    }
+
+    /// Settings the solution of the initial value problem `function` depends on:
+    /// the parameters and the named functions its equation uses, directly or
+    /// through the functions it calls.
+    pub fn solution_key(&self, function: &FnInput) -> SolutionKey {
+        let called = self.called_functions(function);
+        let mut names: Vec<String> = core::iter::once(function)
+            .chain(called.iter().copied())
+            .flat_map(|function| function.kind().free_variables())
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        SolutionKey {
+            domain: self.domain,
+            step: (self.domain.1 - self.domain.0) / self.quality as f64,
+            solver: function.solver,
+            parameters: self
+                .parameters
+                .iter()
+                .filter(|parameter| names.contains(&parameter.name))
+                .map(|parameter| (parameter.name.clone(), parameter.value))
+                .collect(),
+            definitions: called
+                .iter()
+                .map(|function| function.string.clone())
+                .collect(),
+        }
+    }
+
+    /// Named functions that `function` calls, directly or not, in the order they are found.
+    fn called_functions(&self, function: &FnInput) -> Vec<&FnInput> {
+        let mut called: Vec<&FnInput> = vec![];
+        let mut pending = function.kind().calls();
+        while let Some(name) = pending.pop() {
+            let callee = self
+                .functions
+                .iter()
+                .find(|function| function.name() == Some(name.as_str()));
+            if let Some(callee) = callee {
+                if !called.iter().any(|known| core::ptr::eq(*known, callee)) {
+                    called.push(callee);
+                    pending.extend(callee.kind().calls());
+                }
+            }
+        }
+        called
+    }
+
+    /// Makes the parameters match the free variables of the functions:
+    /// new ones get default settings and those no longer used are dropped.
+    pub fn sync_parameters(&mut self) {
+        let mut names: Vec<String> = self
+            .functions
+            .iter()
+            .flat_map(|function| function.kind().free_variables())
+            .collect();
+        if self.mode == Mode::DomainColoring {
+            names.extend(
+                self.complex_function
+                    .variables()
+                    .into_iter()
+                    .filter(|name| name != "z"),
+            );
+        }
+        if self.mode == Mode::PhasePortrait {
+            names.extend(self.phase_portrait.free_variables());
+        }
+        names.sort_unstable();
+        names.dedup();
+        let mut previous = core::mem::take(&mut self.parameters);
+        self.parameters = names
+            .into_iter()
+            .map(|name| match previous.iter().position(|p| p.name == name) {
+                Some(index) => previous.swap_remove(index),
+                None => Parameter::new(name),
+            })
+            .collect();
+    }
+}
+
+impl Default for Input {
     fn default() -> Self {
         let functions = vec![FnInput::default()];
         Self {
+            mode: Mode::default(),
             canvas_size: (360, 360),
-            domain: (-3.14, 3.14),
+            domain: (-core::f64::consts::PI, core::f64::consts::PI),
+            y_domain: (-core::f64::consts::PI, core::f64::consts::PI),
+            range: (-1., 1.),
+            auto_range: true,
             mesh: true,
             x_axis: true,
             y_axis: true,
@@ -54,6 +263,16 @@ impl<'a> Default for Input {
             title_string: "Your function".to_string(),
             quality: 100,
             functions,
+            parameters: Vec::new(),
+            complex_function: ComplexExpression::default(),
+            modulus_contours: false,
+            projection: Projection::default(),
+            heatmap: Heatmap::default(),
+            slope_field_density: 20,
+            phase_portrait: PhasePortrait::default(),
+            key_points: true,
+            tangent: Tangent::default(),
+            fourier: Fourier::default(),
         }
     }
 }
@@ -75,10 +294,10 @@ impl From<yew::format::Text> for Input {
     }
 }
 
-impl Into<yew::format::Text> for &Input {
-    fn into(self) -> yew::format::Text {
+impl From<&Input> for yew::format::Text {
+    fn from(input: &Input) -> Self {
         log::trace!("Transforming Input into Text");
-        Ok(ron::ser::to_string(self)?)
+        Ok(ron::ser::to_string(input)?)
     }
 }
 
@@ -92,6 +311,11 @@ pub enum Set {
     Quality(ChangeData),
     CanvasWidth(ChangeData),
     CanvasHeight(ChangeData),
+    Yaw(ChangeData),
+    Pitch(ChangeData),
+    Scale(ChangeData),
+    SlopeFieldDensity(ChangeData),
+    KeyPoints,
 }
 
 impl Input {
@@ -124,6 +348,10 @@ impl Input {
                 self.y_axis = !self.y_axis;
                 true
             }
+            Set::KeyPoints => {
+                self.key_points = !self.key_points;
+                true
+            }
             Set::CanvasWidth(data) => {
                 if let ChangeData::Value(x) = data {
                     log::trace!("Trying to change canvas width to {}", x);
@@ -140,6 +368,38 @@ impl Input {
                 }
                 true
             }
+            Set::Yaw(data) => {
+                if let ChangeData::Value(x) = data {
+                    log::trace!("Trying to change yaw to {}", x);
+                    let proposal: f64 = x.parse().unwrap();
+                    self.projection.yaw = proposal;
+                }
+                true
+            }
+            Set::Pitch(data) => {
+                if let ChangeData::Value(x) = data {
+                    log::trace!("Trying to change pitch to {}", x);
+                    let proposal: f64 = x.parse().unwrap();
+                    self.projection.pitch = proposal;
+                }
+                true
+            }
+            Set::Scale(data) => {
+                if let ChangeData::Value(x) = data {
+                    log::trace!("Trying to change scale to {}", x);
+                    let proposal: f64 = x.parse().unwrap();
+                    self.projection.scale = proposal;
+                }
+                true
+            }
+            Set::SlopeFieldDensity(data) => {
+                if let ChangeData::Value(x) = data {
+                    log::trace!("Trying to change slope field density to {}", x);
+                    let proposal: usize = x.parse().unwrap();
+                    self.slope_field_density = proposal.max(2);
+                }
+                true
+            }
             Set::Quality(data) => {
                 if let ChangeData::Value(x) = data {
                     log::trace!("Trying to change quality to {}", x);
@@ -151,3 +411,62 @@ impl Input {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solution_key() {
+        let mut input = Input::default();
+        input.functions.clear();
+        for s in &[
+            "y' = f(x) - y, y(0) = 1",
+            "f(x) = a*g(x)",
+            "g(x) = b*x",
+            "h(x) = c*x",
+        ] {
+            let (name, kind) = parse_definition(s).unwrap();
+            let mut function = FnInput::default();
+            function
+                .set_name(name)
+                .set_kind(kind)
+                .set_string(s.to_string());
+            input.functions.push(function);
+        }
+        input.sync_parameters();
+        let key = input.solution_key(&input.functions[0]);
+        let names: Vec<&str> = key
+            .parameters
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        assert_eq!(names, vec!["a", "b"]);
+        assert_eq!(key.definitions.len(), 2);
+
+        // Changes to what the equation does not use keep the key.
+        input.parameters[2].value = 3.;
+        input.functions[3].set_string("h(x) = c*x^2".to_string());
+        assert_eq!(input.solution_key(&input.functions[0]), key);
+    }
+
+    #[test]
+    fn constants() {
+        let mut input = Input::default();
+        input.functions.clear();
+        for s in &["sin(pi*x)", "e^x"] {
+            let mut function = FnInput::default();
+            function
+                .set_kind(s.parse().unwrap())
+                .set_string(s.to_string());
+            input.functions.push(function);
+        }
+        input.sync_parameters();
+        assert!(input.parameters.is_empty());
+
+        let dependencies = input.dependencies();
+        let context = input.context(&dependencies);
+        let value = input.functions[0].kind().eval(&context, 0.5);
+        assert!((value - 1.).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,242 @@
+use super::{FnInput, FnInputKind};
+use core::fmt::Display;
+use thiserror::Error;
+use yew::prelude::*;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum DataFileError {
+    NotText(String),
+    NoData(String),
+}
+impl Display for DataFileError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        match self {
+            DataFileError::NotText(name) => write!(f, "The file {} is not a text file.", name),
+            DataFileError::NoData(name) => {
+                write!(f, "The file {} does not contain numerical data.", name)
+            }
+        }
+    }
+}
+
+/// Table of numbers read from a CSV, TSV or whitespace-separated file,
+/// from which the user picks the columns to plot.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataFile {
+    pub name: String,
+    /// Name of each column, taken from the last header row if there is one.
+    pub headers: Vec<String>,
+    /// Rows of the table, where cells that are not numbers are `NaN`.
+    pub rows: Vec<Vec<f64>>,
+    pub x_column: usize,
+    pub y_columns: Vec<usize>,
+}
+
+impl DataFile {
+    /// Parses `content`, skipping the header rows, which are those before the
+    /// first row made only of numbers.
+    ///
+    /// The separator is the one that splits the first row of numbers into the most
+    /// cells, so that commas in comments or headers do not matter. Cells separated
+    /// by semicolons may use a decimal comma, as in `1,5;2,3`.
+    pub fn parse(name: String, content: &[u8]) -> Result<Self, DataFileError> {
+        let text =
+            core::str::from_utf8(content).map_err(|_| DataFileError::NotText(name.clone()))?;
+        let lines: Vec<&str> = text
+            .lines()
+            .filter(|line| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+            .collect();
+        let (start, separator) = lines
+            .iter()
+            .enumerate()
+            .find_map(|(index, line)| {
+                SEPARATORS
+                    .iter()
+                    .filter_map(|&separator| Some((numbers(line, separator)?.len(), separator)))
+                    .min_by_key(|(cells, _)| core::cmp::Reverse(*cells))
+                    .map(|(_, separator)| (index, separator))
+            })
+            .ok_or_else(|| DataFileError::NoData(name.clone()))?;
+        let rows: Vec<Vec<f64>> = lines[start..]
+            .iter()
+            .map(|line| {
+                split(line, separator)
+                    .iter()
+                    .map(|cell| number(cell, separator).unwrap_or(f64::NAN))
+                    .collect()
+            })
+            .collect();
+
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+        let mut headers = match start.checked_sub(1) {
+            Some(header) => split(lines[header], separator),
+            None => vec![],
+        };
+        headers.resize(width, String::new());
+        for (index, header) in headers.iter_mut().enumerate() {
+            if header.is_empty() {
+                *header = format!("column {}", index + 1);
+            }
+        }
+
+        Ok(DataFile {
+            name,
+            headers,
+            rows,
+            x_column: 0,
+            y_columns: if width > 1 { vec![1] } else { vec![] },
+        })
+    }
+
+    /// Points of the chosen x column against the column `y_column`,
+    /// skipping rows where either value is missing.
+    pub fn points(&self, y_column: usize) -> Vec<(f64, f64)> {
+        self.rows
+            .iter()
+            .filter_map(|row| Some((*row.get(self.x_column)?, *row.get(y_column)?)))
+            .filter(|(x, y)| x.is_finite() && y.is_finite())
+            .collect()
+    }
+
+    /// One function input of points for each chosen y column.
+    pub fn fn_inputs(&self) -> Vec<FnInput> {
+        self.y_columns
+            .iter()
+            .map(|&y_column| {
+                let points = self
+                    .points(y_column)
+                    .iter()
+                    .map(|(x, y)| format!("({}, {})", x, y))
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                let string = format!("[{}]", points);
+                let kind: FnInputKind = string.parse().unwrap_or_default();
+                let mut fn_input = FnInput::default();
+                fn_input.set_kind(kind).set_string(string);
+                fn_input
+            })
+            .collect()
+    }
+
+    /// One function input with the sample of numbers of each chosen y column,
+    /// skipping missing values.
+    pub fn sample_inputs(&self) -> Vec<FnInput> {
+        self.y_columns
+            .iter()
+            .map(|&y_column| {
+                let values = self
+                    .rows
+                    .iter()
+                    .filter_map(|row| row.get(y_column))
+                    .filter(|value| value.is_finite())
+                    .map(f64::to_string)
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                let string = format!("[{}]", values);
+                let kind: FnInputKind = string.parse().unwrap_or_default();
+                let mut fn_input = FnInput::default();
+                fn_input.set_kind(kind).set_string(string);
+                fn_input
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug)]
+pub enum SetDataFile {
+    XColumn(ChangeData),
+    ToggleYColumn(usize),
+}
+
+impl DataFile {
+    pub fn update(&mut self, set: SetDataFile) -> ShouldRender {
+        match set {
+            SetDataFile::XColumn(data) => {
+                if let ChangeData::Select(element) = data {
+                    log::trace!("Trying to change x column to {}", element.value());
+                    if let Ok(proposal) = element.value().parse::<usize>() {
+                        self.x_column = proposal;
+                    }
+                }
+                true
+            }
+            SetDataFile::ToggleYColumn(column) => {
+                match self.y_columns.iter().position(|&y| y == column) {
+                    Some(index) => {
+                        self.y_columns.remove(index);
+                    }
+                    None => {
+                        self.y_columns.push(column);
+                        self.y_columns.sort_unstable();
+                    }
+                }
+                true
+            }
+        }
+    }
+}
+
+/// Separators of the cells of a row, by priority, where `None` stands for whitespace.
+const SEPARATORS: [Option<char>; 4] = [Some('\t'), Some(';'), Some(','), None];
+
+fn split(line: &str, separator: Option<char>) -> Vec<String> {
+    match separator {
+        Some(separator) => line
+            .split(separator)
+            .map(|cell| cell.trim().to_string())
+            .collect(),
+        None => line.split_whitespace().map(str::to_string).collect(),
+    }
+}
+
+/// Value of `cell`, where a decimal comma is allowed if cells are separated by semicolons.
+fn number(cell: &str, separator: Option<char>) -> Option<f64> {
+    match separator {
+        Some(';') => cell.replace(',', ".").parse().ok(),
+        _ => cell.parse().ok(),
+    }
+}
+
+/// Values of the cells of `line`, if they are all numbers.
+fn numbers(line: &str, separator: Option<char>) -> Option<Vec<f64>> {
+    split(line, separator)
+        .iter()
+        .map(|cell| number(cell, separator))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats() {
+        let csv = "# measurements\ntime, speed, force\n0, 1.5, 2\n1, 2.5, x\n\n2, 3.5, 4\n";
+        let file = DataFile::parse("lab.csv".to_string(), csv.as_bytes()).unwrap();
+        assert_eq!(file.headers, vec!["time", "speed", "force"]);
+        assert_eq!(file.rows.len(), 3);
+        assert_eq!(file.points(1), vec![(0., 1.5), (1., 2.5), (2., 3.5)]);
+        assert_eq!(file.points(2), vec![(0., 2.), (2., 4.)]);
+
+        let tsv = "1\t2\t3\n4\t5\t6";
+        let file = DataFile::parse("lab.tsv".to_string(), tsv.as_bytes()).unwrap();
+        assert_eq!(file.headers, vec!["column 1", "column 2", "column 3"]);
+        assert_eq!(file.points(2), vec![(1., 3.), (4., 6.)]);
+
+        let whitespace = "x y\n  1   2\n3 4";
+        let file = DataFile::parse("lab.dat".to_string(), whitespace.as_bytes()).unwrap();
+        assert_eq!(file.fn_inputs()[0].string, "[(1, 2), (3, 4)]");
+        assert_eq!(file.sample_inputs()[0].string, "[2, 4]");
+
+        let commented = "# speed, in m/s\nt v\n0 1\n1 3";
+        let file = DataFile::parse("lab.dat".to_string(), commented.as_bytes()).unwrap();
+        assert_eq!(file.headers, vec!["t", "v"]);
+        assert_eq!(file.points(1), vec![(0., 1.), (1., 3.)]);
+
+        let decimal_comma = "x;y\n1,5;2,3\n2;4,5";
+        let file = DataFile::parse("lab.csv".to_string(), decimal_comma.as_bytes()).unwrap();
+        assert_eq!(file.points(1), vec![(1.5, 2.3), (2., 4.5)]);
+
+        assert!(DataFile::parse("empty.csv".to_string(), b"a, b\n").is_err());
+    }
+}
@@ -0,0 +1,186 @@
+use super::FnInput;
+use core::fmt::Display;
+use std::collections::HashMap;
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum DependencyError {
+    DuplicateName(String),
+    UnknownFunction(String),
+    /// Names of the functions in the cycle, starting and ending with the same one.
+    Cycle(Vec<String>),
+    InvalidDependency(String),
+}
+impl Display for DependencyError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        match self {
+            DependencyError::DuplicateName(name) => {
+                write!(f, "The function {} is defined more than once.", name)
+            }
+            DependencyError::UnknownFunction(name) => {
+                write!(f, "The function {} is not defined.", name)
+            }
+            DependencyError::Cycle(names) => {
+                write!(f, "Circular definition: {}.", names.join(" -> "))
+            }
+            DependencyError::InvalidDependency(name) => {
+                write!(f, "The function {} can not be evaluated.", name)
+            }
+        }
+    }
+}
+
+/// Dependency graph of the function inputs, given by the calls between them.
+#[derive(Debug, Clone, Default)]
+pub struct Dependencies {
+    /// Indices of the functions that can be evaluated, each one after those it calls.
+    pub order: Vec<usize>,
+    /// Reason why each of the remaining functions can not be evaluated.
+    pub errors: HashMap<usize, DependencyError>,
+}
+
+enum State {
+    InProgress,
+    Done(Result<(), DependencyError>),
+}
+
+impl Dependencies {
+    pub fn new(functions: &[FnInput]) -> Self {
+        let mut dependencies = Dependencies::default();
+        let mut names = HashMap::new();
+        for (index, function) in functions.iter().enumerate() {
+            if let Some(name) = function.name() {
+                if names.contains_key(name) {
+                    let error = DependencyError::DuplicateName(name.to_string());
+                    dependencies.errors.insert(index, error);
+                } else {
+                    names.insert(name, index);
+                }
+            }
+        }
+
+        let mut states: Vec<Option<State>> = (0..functions.len())
+            .map(|index| {
+                dependencies
+                    .errors
+                    .get(&index)
+                    .map(|error| State::Done(Err(error.clone())))
+            })
+            .collect();
+        let mut stack = Vec::new();
+        for index in 0..functions.len() {
+            if states[index].is_none() {
+                // Errors are recorded by the visit itself
+                let _ = dependencies.visit(index, functions, &names, &mut states, &mut stack);
+            }
+        }
+        dependencies
+    }
+
+    /// Depth first search, recording the functions in post-order.
+    fn visit(
+        &mut self,
+        index: usize,
+        functions: &[FnInput],
+        names: &HashMap<&str, usize>,
+        states: &mut [Option<State>],
+        stack: &mut Vec<usize>,
+    ) -> Result<(), DependencyError> {
+        if let Some(State::Done(result)) = &states[index] {
+            return result.clone();
+        }
+        states[index] = Some(State::InProgress);
+        stack.push(index);
+
+        let mut result = Ok(());
+        for name in functions[index].kind().calls() {
+            let callee = match names.get(name.as_str()) {
+                Some(callee) => *callee,
+                None => {
+                    result = Err(DependencyError::UnknownFunction(name));
+                    break;
+                }
+            };
+            if let Some(State::InProgress) = states[callee] {
+                let start = stack.iter().position(|i| *i == callee).unwrap();
+                let cycle = stack[start..]
+                    .iter()
+                    .map(|i| functions[*i].name().unwrap_or_default().to_string())
+                    .chain(Some(name))
+                    .collect();
+                result = Err(DependencyError::Cycle(cycle));
+                break;
+            }
+            if let Err(error) = self.visit(callee, functions, names, states, stack) {
+                let in_cycle = match (&error, functions[index].name()) {
+                    (DependencyError::Cycle(cycle), Some(own_name)) => {
+                        cycle.iter().any(|name| name == own_name)
+                    }
+                    _ => false,
+                };
+                result = Err(if in_cycle {
+                    error
+                } else {
+                    DependencyError::InvalidDependency(name)
+                });
+                break;
+            }
+        }
+
+        stack.pop();
+        match &result {
+            Ok(()) => self.order.push(index),
+            Err(error) => {
+                self.errors.insert(index, error.clone());
+            }
+        }
+        states[index] = Some(State::Done(result.clone()));
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::fn_input::parse_definition;
+
+    fn functions(strings: &[&str]) -> Vec<FnInput> {
+        strings
+            .iter()
+            .map(|s| {
+                let (name, kind) = parse_definition(s).unwrap();
+                let mut fn_input = FnInput::default();
+                fn_input.set_name(name).set_kind(kind);
+                fn_input
+            })
+            .collect()
+    }
+
+    #[test]
+    fn cycle() {
+        let functions = functions(&[
+            "h(x) = g(x) + 1",
+            "f(x) = g(x)",
+            "g(x) = f(x)^2",
+            "k(x) = sin(x)",
+            "h(x) = k(x)",
+            "unknown(x) + x",
+        ]);
+        let dependencies = Dependencies::new(&functions);
+        assert_eq!(dependencies.order, vec![3]);
+        assert_eq!(
+            dependencies.errors[&0],
+            DependencyError::InvalidDependency("g".to_string())
+        );
+        assert!(matches!(dependencies.errors[&1], DependencyError::Cycle(_)));
+        assert!(matches!(dependencies.errors[&2], DependencyError::Cycle(_)));
+        assert_eq!(
+            dependencies.errors[&4],
+            DependencyError::DuplicateName("h".to_string())
+        );
+        assert_eq!(
+            dependencies.errors[&5],
+            DependencyError::UnknownFunction("unknown".to_string())
+        );
+    }
+}
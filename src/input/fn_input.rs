@@ -1,35 +1,257 @@
+use core::convert::TryFrom;
 use core::fmt::Display;
 use core::str::FromStr;
 use exmex::OwnedFlatEx;
 use serde::{Deserialize, Serialize};
-use splines::{interpolation::Interpolation, key::Key, spline::Spline};
+use splines::spline::Spline;
+use std::cell::RefCell;
 use thiserror::Error;
 
+mod complex;
+mod condition;
+mod expression;
+pub mod fit;
+pub mod initial_value_problem;
+mod integral;
+pub mod piecewise;
+mod point_style;
+pub mod points;
+mod sample;
+pub mod slope_field;
+mod solver;
+pub mod taylor;
+pub use complex::ComplexExpression;
+pub use condition::Condition;
+pub use expression::{Context, Expression};
+pub use fit::{Fit, FitModel, FitResult, SetFit, MAX_FIT_DEGREE};
+pub use integral::{Integral, SetIntegral};
+pub use piecewise::Branch;
+pub use point_style::{DisplayMode, MarkerShape, PointStyle, SetPointStyle};
+pub use points::Interpolation;
+pub use sample::{Binning, Distribution, SetDistribution};
+pub use solver::{SetSolver, SolutionKey, Solver};
+pub use taylor::{SetTaylor, Taylor, MAX_TAYLOR_ORDER};
+
 const DEFAULT_INPUT: &str = "sin(x)";
 
+/// Highest order of the derivatives that can be drawn along with a function.
+pub const MAX_DERIVATIVES: usize = 4;
+
+/// Highest order of the Fourier series that can be drawn along with a function.
+pub const MAX_FOURIER_ORDER: usize = 50;
+
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum FnInputKind {
-    Analytical { expression: OwnedFlatEx<f64> },
-    Points { spline: Spline<f64, f64> },
+    Analytical {
+        expression: Expression,
+    },
+    Points {
+        spline: Spline<f64, f64>,
+        #[serde(default)]
+        interpolation: Interpolation,
+    },
+    Parametric {
+        x: Expression,
+        y: Expression,
+        range: ParameterRange,
+    },
+    Polar {
+        radius: Expression,
+        range: ParameterRange,
+    },
+    /// Conditional function, whose value is given by the first branch that applies.
+    Piecewise {
+        branches: Vec<Branch>,
+    },
+    /// Zero set of `expression`, a function of `x` and `y`.
+    Implicit {
+        expression: Expression,
+    },
+    /// Curve `x = expression`, where `expression` is a function of `y`.
+    FunctionOfY {
+        expression: Expression,
+    },
+    /// Surface `z = expression`, where `expression` is a function of `x` and `y`.
+    Surface {
+        expression: Expression,
+    },
+    /// Curve in space, parametrized by `t`.
+    Parametric3d {
+        x: Expression,
+        y: Expression,
+        z: Expression,
+        range: ParameterRange,
+    },
+    /// Region of the plane where `condition`, made of inequalities in `x` and `y`, holds.
+    Inequality {
+        condition: Condition,
+    },
+    /// Direction field of the differential equation `y' = expression`, a function
+    /// of `x` and `y`, with the solutions through the initial conditions.
+    SlopeField {
+        expression: Expression,
+        initial_conditions: Vec<(f64, f64)>,
+    },
+    /// Solution of the differential equation `y^(n) = expression`, a function of `x`,
+    /// `y` and its derivatives up to the order `n - 1`, whose values at `x0` are
+    /// `initial_values`.
+    InitialValueProblem {
+        expression: Expression,
+        x0: f64,
+        initial_values: Vec<f64>,
+    },
+    /// Sample of numbers, sorted, drawn as a distribution.
+    Sample {
+        values: Vec<f64>,
+    },
 }
 impl Default for FnInputKind {
     fn default() -> Self {
-        let string = DEFAULT_INPUT.to_string();
         FnInputKind::Analytical {
-            expression: OwnedFlatEx::from_flatex(
-                exmex::parse::<f64>(&string, &exmex::make_default_operators::<f64>()).unwrap(),
-            ),
+            expression: DEFAULT_INPUT.parse().unwrap(),
         }
     }
 }
 
+impl FnInputKind {
+    /// Whether the kind is a function of `x`, so that it can be given a name and called.
+    pub fn is_callable(&self) -> bool {
+        matches!(
+            self,
+            FnInputKind::Analytical { .. }
+                | FnInputKind::Points { .. }
+                | FnInputKind::Piecewise { .. }
+        )
+    }
+
+    /// Whether the kind is drawn in space rather than in the plane.
+    pub fn is_3d(&self) -> bool {
+        matches!(
+            self,
+            FnInputKind::Surface { .. } | FnInputKind::Parametric3d { .. }
+        )
+    }
+
+    /// Value at `x` of a function of `x`, or `NaN` for other kinds.
+    pub fn eval(&self, context: &Context, x: f64) -> f64 {
+        match self {
+            FnInputKind::Analytical { expression } => expression.eval(context, &[("x", x)]),
+            FnInputKind::Points {
+                spline,
+                interpolation,
+            } => points::eval(spline, *interpolation, x),
+            FnInputKind::Piecewise { branches } => piecewise::eval(branches, context, x),
+            _ => f64::NAN,
+        }
+    }
+
+    /// Symbolic derivatives of orders one to `order`, if the input is an analytical
+    /// function that does not call other functions.
+    pub fn derivative_expressions(&self, order: usize) -> Option<Vec<Expression>> {
+        match self {
+            FnInputKind::Analytical { expression } => {
+                let mut derivatives: Vec<Expression> = Vec::with_capacity(order);
+                for _ in 0..order {
+                    let derivative = derivatives.last().unwrap_or(expression).derivative("x")?;
+                    derivatives.push(derivative);
+                }
+                Some(derivatives)
+            }
+            _ => None,
+        }
+    }
+
+    /// All the expressions defining the input.
+    pub fn expressions(&self) -> Vec<&Expression> {
+        match self {
+            FnInputKind::Analytical { expression }
+            | FnInputKind::Implicit { expression }
+            | FnInputKind::FunctionOfY { expression }
+            | FnInputKind::Surface { expression }
+            | FnInputKind::SlopeField { expression, .. }
+            | FnInputKind::InitialValueProblem { expression, .. } => vec![expression],
+            FnInputKind::Points { .. } | FnInputKind::Sample { .. } => vec![],
+            FnInputKind::Parametric { x, y, .. } => vec![x, y],
+            FnInputKind::Parametric3d { x, y, z, .. } => vec![x, y, z],
+            FnInputKind::Polar { radius, .. } => vec![radius],
+            FnInputKind::Piecewise { branches } => {
+                branches.iter().flat_map(Branch::expressions).collect()
+            }
+            FnInputKind::Inequality { condition } => condition.expressions(),
+        }
+    }
+
+    /// Names of the functions this one calls.
+    pub fn calls(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .expressions()
+            .into_iter()
+            .flat_map(Expression::calls)
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+
+    /// Variables that are given a value when evaluating this kind of input.
+    pub fn bound_variables(&self) -> &'static [&'static str] {
+        match self {
+            FnInputKind::Analytical { .. }
+            | FnInputKind::Points { .. }
+            | FnInputKind::Piecewise { .. } => &["x"],
+            FnInputKind::Parametric { .. } | FnInputKind::Parametric3d { .. } => &["t"],
+            FnInputKind::Polar { .. } => &["theta"],
+            FnInputKind::FunctionOfY { .. } => &["y"],
+            FnInputKind::Implicit { .. }
+            | FnInputKind::Inequality { .. }
+            | FnInputKind::Surface { .. }
+            | FnInputKind::SlopeField { .. } => &["x", "y"],
+            FnInputKind::InitialValueProblem { .. } => &["x", "y", "y_1", "y_2", "y_3"],
+            FnInputKind::Sample { .. } => &[],
+        }
+    }
+
+    /// Variables that are not bound, which are treated as parameters.
+    pub fn free_variables(&self) -> Vec<String> {
+        let bound = self.bound_variables();
+        let mut names: Vec<String> = self
+            .expressions()
+            .into_iter()
+            .flat_map(Expression::variables)
+            .filter(|name| !bound.contains(&name.as_str()))
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+}
+
+/// Range of values of the free parameter of a curve, together with
+/// the number of samples to take from it.
+///
+/// If `samples` is `None`, the quality of the plot is used instead.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParameterRange {
+    pub start: f64,
+    pub end: f64,
+    pub samples: Option<usize>,
+}
+
+impl ParameterRange {
+    pub fn grid(&self, quality: usize) -> itertools_num::Linspace<f64> {
+        itertools_num::linspace(self.start, self.end, self.samples.unwrap_or(quality))
+    }
+}
+
 #[derive(Error, Debug)]
 pub struct FormatError(String);
 impl Display for FormatError {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
         write!(
             f,
-            "The input was not an anlytical function nor a collection of points."
+            "The input {:?} does not match any of the available formats.",
+            self.0
         )
     }
 }
@@ -37,20 +259,66 @@ impl Display for FormatError {
 impl FromStr for FnInputKind {
     type Err = FormatError;
     fn from_str(s: &str) -> Result<Self, FormatError> {
-        if let Ok(expr) = exmex::parse_with_default_ops(&s.to_string()) {
-            log::debug!("We noticed an analyical function :)");
-            let expression = OwnedFlatEx::from_flatex(expr);
-            Ok(FnInputKind::Analytical { expression })
+        if let Ok(expression) = Expression::from_str(s) {
+            if is_implicit(&expression) {
+                log::debug!("We noticed an implicit curve");
+                Ok(FnInputKind::Implicit { expression })
+            } else {
+                log::debug!("We noticed an analyical function :)");
+                Ok(FnInputKind::Analytical { expression })
+            }
         } else {
             log::debug!("We noticed it was not an analyical function.");
-            if let Ok(values) = ron::de::from_str::<Vec<(f64, f64)>>(s) {
-                let spline = Spline::from_iter(
-                    values
-                        .iter()
-                        .map(|(x, y)| Key::new(*x, *y, Interpolation::Cosine)),
-                );
+            if let Some((values, interpolation)) = points::parse_points(s) {
+                let spline = points::spline(&values, interpolation);
                 log::debug!("We noticed a collection of points");
-                Ok(FnInputKind::Points { spline })
+                Ok(FnInputKind::Points {
+                    spline,
+                    interpolation,
+                })
+            } else if let Some(values) = sample::parse_sample(s) {
+                log::debug!("We noticed a sample of numbers");
+                Ok(FnInputKind::Sample { values })
+            } else if let Some((expression, initial_conditions)) = slope_field::parse_slope_field(s)
+            {
+                log::debug!("We noticed a differential equation");
+                Ok(FnInputKind::SlopeField {
+                    expression,
+                    initial_conditions,
+                })
+            } else if let Some((expression, x0, initial_values)) =
+                initial_value_problem::parse_initial_value_problem(s)
+            {
+                log::debug!("We noticed an initial value problem");
+                Ok(FnInputKind::InitialValueProblem {
+                    expression,
+                    x0,
+                    initial_values,
+                })
+            } else if let Some(branches) = piecewise::parse_branches(s) {
+                log::debug!("We noticed a piecewise function");
+                Ok(FnInputKind::Piecewise { branches })
+            } else if let Some(kind) = parse_parametric(s) {
+                log::debug!("We noticed a parametric curve");
+                Ok(kind)
+            } else if let Some(kind) = parse_polar(s) {
+                log::debug!("We noticed a polar curve");
+                Ok(kind)
+            } else if let Some(kind) = parse_inequality(s) {
+                log::debug!("We noticed an inequality");
+                Ok(kind)
+            } else if let Some(kind) = parse_surface(s) {
+                log::debug!("We noticed a surface");
+                Ok(kind)
+            } else if let Some(kind) = parse_function_of_y(s) {
+                log::debug!("We noticed a function of y");
+                Ok(kind)
+            } else if let Some(kind) = parse_function_of_x(s) {
+                log::debug!("We noticed an analyical function :)");
+                Ok(kind)
+            } else if let Some(kind) = parse_implicit(s) {
+                log::debug!("We noticed an implicit curve");
+                Ok(kind)
             } else {
                 Err(FormatError(s.to_string()))
             }
@@ -58,20 +326,330 @@ impl FromStr for FnInputKind {
     }
 }
 
+/// Parses inputs of the form `(x(t), y(t)), t in [start, end]`, or
+/// `(x(t), y(t), z(t)), t in [start, end]` for curves in space,
+/// optionally followed by `, samples = n`.
+fn parse_parametric(s: &str) -> Option<FnInputKind> {
+    let parts = split_top_level(s, ",");
+    if !(2..=3).contains(&parts.len()) {
+        return None;
+    }
+    let coordinates = parts[0].trim().strip_prefix('(')?.strip_suffix(')')?;
+    let coordinates = split_top_level(coordinates, ",")
+        .into_iter()
+        .map(parse_without_coordinates)
+        .collect::<Option<Vec<Expression>>>()?;
+    let (start, end) = parse_interval(parts[1], "t")?;
+    let samples = match parts.get(2) {
+        Some(part) => Some(parse_samples(part)?),
+        None => None,
+    };
+    let range = ParameterRange {
+        start,
+        end,
+        samples,
+    };
+
+    match <[Expression; 3]>::try_from(coordinates) {
+        Ok([x, y, z]) => Some(FnInputKind::Parametric3d { x, y, z, range }),
+        Err(coordinates) => {
+            let [x, y] = <[Expression; 2]>::try_from(coordinates).ok()?;
+            Some(FnInputKind::Parametric { x, y, range })
+        }
+    }
+}
+
+/// Parses inputs of the form `r = r(theta)`, optionally followed by
+/// `, theta in [start, end]` and `, samples = n`.
+///
+/// The default range is `[0, 2pi]`.
+fn parse_polar(s: &str) -> Option<FnInputKind> {
+    let s = s.replace('θ', "theta");
+    let parts = split_top_level(&s, ",");
+    let radius = parts[0].trim().strip_prefix('r')?.trim_start();
+    let radius = parse_without_coordinates(radius.strip_prefix('=')?)?;
+
+    let mut range = ParameterRange {
+        start: 0.,
+        end: 2. * core::f64::consts::PI,
+        samples: None,
+    };
+    match &parts[1..] {
+        [] => (),
+        [part] => {
+            if let Some((start, end)) = parse_interval(part, "theta") {
+                range.start = start;
+                range.end = end;
+            } else {
+                range.samples = Some(parse_samples(part)?);
+            }
+        }
+        [interval, samples] => {
+            let (start, end) = parse_interval(interval, "theta")?;
+            range.start = start;
+            range.end = end;
+            range.samples = Some(parse_samples(samples)?);
+        }
+        _ => return None,
+    }
+
+    Some(FnInputKind::Polar { radius, range })
+}
+
+/// Parses equations of the form `z = f(x, y)`.
+fn parse_surface(s: &str) -> Option<FnInputKind> {
+    let (lhs, rhs) = s.split_once('=')?;
+    if lhs.trim() != "z" || rhs.starts_with('=') {
+        return None;
+    }
+    let expression: Expression = rhs.parse().ok()?;
+    if expression.uses("z") {
+        None
+    } else {
+        Some(FnInputKind::Surface { expression })
+    }
+}
+
+/// Parses equations of the form `x = g(y)`, such as the vertical line `x = 2`.
+fn parse_function_of_y(s: &str) -> Option<FnInputKind> {
+    let (lhs, rhs) = s.split_once('=')?;
+    if lhs.trim() != "x" || rhs.starts_with('=') {
+        return None;
+    }
+    let expression: Expression = rhs.parse().ok()?;
+    if expression.uses("x") {
+        None
+    } else {
+        Some(FnInputKind::FunctionOfY { expression })
+    }
+}
+
+/// Parses equations of the form `y = f(x)`, which are sampled as analytical functions
+/// rather than traced as implicit curves.
+fn parse_function_of_x(s: &str) -> Option<FnInputKind> {
+    let (lhs, rhs) = s.split_once('=')?;
+    if lhs.trim() != "y" || rhs.starts_with('=') {
+        return None;
+    }
+    let expression: Expression = rhs.parse().ok()?;
+    if expression.uses("y") {
+        None
+    } else {
+        Some(FnInputKind::Analytical { expression })
+    }
+}
+
+/// Parses equations of the form `F(x, y) = G(x, y)`, which must involve `x` or `y`.
+fn parse_implicit(s: &str) -> Option<FnInputKind> {
+    let (lhs, rhs) = s.split_once('=')?;
+    let expression: Expression = format!("({}) - ({})", lhs, rhs).parse().ok()?;
+    if expression.uses("x") || expression.uses("y") {
+        Some(FnInputKind::Implicit { expression })
+    } else {
+        None
+    }
+}
+
+/// Parses inequalities combined with `&&` and `||`, as in `y > x && y < 2x`.
+fn parse_inequality(s: &str) -> Option<FnInputKind> {
+    let condition: Condition = s.parse().ok()?;
+    if condition
+        .comparisons()
+        .all(|comparison| comparison.relation.is_order())
+    {
+        Some(FnInputKind::Inequality { condition })
+    } else {
+        None
+    }
+}
+
+/// Whether `expression` depends on `y`, in which case it is not a function of `x`.
+fn is_implicit(expression: &Expression) -> bool {
+    expression.uses("y")
+}
+
+/// Parses `variable in [start, end]`.
+fn parse_interval(s: &str, variable: &str) -> Option<(f64, f64)> {
+    let interval = s.trim().strip_prefix(variable)?.trim_start();
+    let interval = interval
+        .strip_prefix("in")?
+        .trim()
+        .strip_prefix('[')?
+        .strip_suffix(']')?;
+    let bounds = split_top_level(interval, ",");
+    if bounds.len() != 2 {
+        return None;
+    }
+    Some((parse_constant(bounds[0])?, parse_constant(bounds[1])?))
+}
+
+/// Parses `samples = n`, with at least two samples.
+fn parse_samples(s: &str) -> Option<usize> {
+    let (key, value) = s.split_once('=')?;
+    if key.trim() != "samples" {
+        return None;
+    }
+    let samples: usize = value.trim().parse().ok()?;
+    if samples < 2 {
+        None
+    } else {
+        Some(samples)
+    }
+}
+
+/// Parses an expression of a curve parameter, which does not use the coordinates `x` and `y`.
+fn parse_without_coordinates(s: &str) -> Option<Expression> {
+    let expression: Expression = s.parse().ok()?;
+    if expression.uses("x") || expression.uses("y") {
+        None
+    } else {
+        Some(expression)
+    }
+}
+
+/// Splits a definition `name(x) = body` into its name and body.
+///
+/// Names of `exmex` operators, like `sin`, are not valid names.
+fn split_definition(s: &str) -> Option<(&str, &str)> {
+    let s = s.trim_start();
+    let end = s
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or(s.len());
+    let (name, rest) = s.split_at(end);
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        || exmex::make_default_operators::<f64>()
+            .iter()
+            .any(|operator| operator.repr == name)
+    {
+        return None;
+    }
+    let rest = rest.trim_start().strip_prefix('(')?.trim_start();
+    let rest = rest.strip_prefix('x')?.trim_start().strip_prefix(')')?;
+    let body = rest.trim_start().strip_prefix('=')?;
+    if body.starts_with('=') {
+        return None;
+    }
+    Some((name, body))
+}
+
+/// Parses an input that is either a definition `name(x) = body`, where `body` is
+/// a function of `x`, or an anonymous function input.
+pub fn parse_definition(s: &str) -> Result<(Option<String>, FnInputKind), FormatError> {
+    if let Some((name, body)) = split_definition(s) {
+        if let Ok(kind) = FnInputKind::from_str(body.trim()) {
+            if kind.is_callable() {
+                log::debug!("We noticed a definition of {}", name);
+                return Ok((Some(name.to_string()), kind));
+            }
+        }
+    }
+    FnInputKind::from_str(s).map(|kind| (None, kind))
+}
+
+/// Parses a real constant, where `pi` (or `π`) is allowed, as in `2pi` or `-pi/2`.
+pub fn parse_constant(s: &str) -> Option<f64> {
+    let s = s.trim().replace('π', "pi");
+    let mut text = String::with_capacity(s.len());
+    let mut previous = ' ';
+    for (i, c) in s.char_indices() {
+        if s[i..].starts_with("pi") && (previous.is_ascii_digit() || previous == ')') {
+            text.push('*');
+        }
+        text.push(c);
+        previous = c;
+    }
+    let expression = OwnedFlatEx::from_flatex(exmex::parse_with_default_ops(&text).ok()?);
+    match expression::variables(&expression).as_slice() {
+        [] => expression.eval(&[]).ok(),
+        [name] if name == "pi" => expression.eval(&[core::f64::consts::PI]).ok(),
+        _ => None,
+    }
+    .filter(|value| value.is_finite())
+}
+
+/// Splits `s` at the occurrences of `separator` that are not nested
+/// inside parentheses or brackets.
+pub fn split_top_level<'a>(s: &'a str, separator: &str) -> Vec<&'a str> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut last = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            _ if depth == 0 && i >= last && s[i..].starts_with(separator) => {
+                parts.push(&s[last..i]);
+                last = i + separator.len();
+            }
+            _ => (),
+        }
+    }
+    parts.push(&s[last..]);
+    parts
+}
+
+/// Points of the solution of an initial value problem, with the settings they were computed for.
+type Solution = (SolutionKey, Vec<(f64, f64)>);
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FnInput {
     pub show: bool,
     pub string: String,
     pub kind: FnInputKind,
+    /// Name under which other inputs can call this one.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// How the input is drawn if it is point data.
+    #[serde(default)]
+    pub point_style: PointStyle,
+    /// Number of derivatives drawn along with the input, if it is a function of `x`.
+    #[serde(default)]
+    pub derivatives: usize,
+    /// Integrals drawn along with the input, if it is a function of `x`.
+    #[serde(default)]
+    pub integral: Integral,
+    /// Taylor polynomial drawn along with the input, if it is analytical.
+    #[serde(default)]
+    pub taylor: Taylor,
+    /// Order of the Fourier series over the domain drawn along with the input,
+    /// if it is analytical, or zero for none.
+    #[serde(default)]
+    pub fourier_series: usize,
+    /// Least-squares fit drawn along with the input, if it is point data.
+    #[serde(default)]
+    pub fit: Fit,
+    /// How the input is drawn if it is a sample of numbers.
+    #[serde(default)]
+    pub distribution: Distribution,
+    /// How the input is solved if it is an initial value problem.
+    #[serde(default)]
+    pub solver: Solver,
+    /// Last solution computed, if the input is an initial value problem.
+    #[serde(skip)]
+    solution: RefCell<Option<Solution>>,
+    /// Symbolic derivatives computed so far, if the input is analytical.
+    #[serde(skip)]
+    derivative_cache: RefCell<Vec<Expression>>,
 }
 
 impl Default for FnInput {
     fn default() -> Self {
         let string = DEFAULT_INPUT.to_string();
         FnInput {
-            string: string,
+            string,
             show: true,
             kind: FnInputKind::default(),
+            name: None,
+            point_style: PointStyle::default(),
+            derivatives: 0,
+            integral: Integral::default(),
+            taylor: Taylor::default(),
+            fourier_series: 0,
+            fit: Fit::default(),
+            distribution: Distribution::default(),
+            solver: Solver::default(),
+            solution: RefCell::default(),
+            derivative_cache: RefCell::default(),
         }
     }
 }
@@ -91,12 +669,53 @@ impl FnInput {
     }
     pub fn set_kind(&mut self, kind: FnInputKind) -> &mut Self {
         self.kind = kind;
+        self.solution.take();
+        self.derivative_cache.take();
         self
     }
     pub fn set_string(&mut self, s: String) -> &mut Self {
         self.string = s;
         self
     }
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+    pub fn set_name(&mut self, name: Option<String>) -> &mut Self {
+        self.name = name;
+        self
+    }
+    /// Symbolic derivatives of orders one to `order`, as given by
+    /// [`FnInputKind::derivative_expressions`], but only computing those of orders
+    /// not asked for before, since each one is larger than the previous.
+    pub fn derivative_expressions(&self, order: usize) -> Option<Vec<Expression>> {
+        let expression = match &self.kind {
+            FnInputKind::Analytical { expression } => expression,
+            _ => return None,
+        };
+        let mut derivatives = self.derivative_cache.borrow_mut();
+        while derivatives.len() < order {
+            let derivative = derivatives.last().unwrap_or(expression).derivative("x")?;
+            derivatives.push(derivative);
+        }
+        Some(derivatives[..order].to_vec())
+    }
+    /// Solution of the initial value problem for `key`, computed by `solve`
+    /// unless the one computed last was for the same key.
+    pub fn solution<F>(&self, key: SolutionKey, solve: F) -> Vec<(f64, f64)>
+    where
+        F: FnOnce() -> Vec<(f64, f64)>,
+    {
+        let mut solution = self.solution.borrow_mut();
+        match &*solution {
+            Some((previous, points)) if *previous == key => points.clone(),
+            _ => {
+                log::trace!("Solving the initial value problem {}", self.string);
+                let points = solve();
+                *solution = Some((key, points.clone()));
+                points
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -108,4 +727,182 @@ mod tests {
         let mut fn_input = FnInput::default();
         assert_eq!(fn_input.show(), !fn_input.toggle().show());
     }
+
+    #[test]
+    fn derivative_cache() {
+        let mut fn_input = FnInput::default();
+        fn_input.set_kind(FnInputKind::from_str("x^3").unwrap());
+        let context = Context::default();
+        let third = fn_input.derivative_expressions(3).unwrap();
+        assert_eq!(third[2].eval(&context, &[("x", 1.)]), 6.);
+        assert_eq!(fn_input.derivative_expressions(1).unwrap().len(), 1);
+        // Derivatives of the previous kind are not reused.
+        fn_input.set_kind(FnInputKind::from_str("x^2").unwrap());
+        let second = fn_input.derivative_expressions(2).unwrap();
+        assert_eq!(second[1].eval(&context, &[("x", 1.)]), 2.);
+    }
+
+    #[test]
+    fn parametric() {
+        let kind = FnInputKind::from_str("(cos(t), sin(t)), t in [0, 2pi]").unwrap();
+        if let FnInputKind::Parametric { x, y, range } = kind {
+            let context = Context::default();
+            assert_eq!(x.eval(&context, &[("t", 0.)]), 1.);
+            assert_eq!(y.eval(&context, &[("t", 0.)]), 0.);
+            assert_eq!(range.start, 0.);
+            assert_eq!(range.end, 2. * core::f64::consts::PI);
+            assert_eq!(range.samples, None);
+        } else {
+            panic!("Expected a parametric curve, found {:?}", kind);
+        }
+        let kind = FnInputKind::from_str("(t, 1), t in [-1, 1], samples = 10").unwrap();
+        assert!(matches!(kind, FnInputKind::Parametric { range, .. } if range.samples == Some(10)));
+        let kind = FnInputKind::from_str("(a*cos(t), sin(t)), t in [0, 1]").unwrap();
+        assert_eq!(kind.free_variables(), vec!["a".to_string()]);
+        assert!(FnInputKind::from_str("(cos(x), sin(t)), t in [0, 1]").is_err());
+    }
+
+    #[test]
+    fn sample() {
+        let kind = FnInputKind::from_str("[3.5, -1, 2e1]").unwrap();
+        assert!(matches!(kind, FnInputKind::Sample { values } if values == vec![-1., 3.5, 20.]));
+        let kind = FnInputKind::from_str("[(0, 1), (1, 2)]").unwrap();
+        assert!(matches!(kind, FnInputKind::Points { .. }));
+        assert!(!matches!(
+            FnInputKind::from_str("[]"),
+            Ok(FnInputKind::Sample { .. })
+        ));
+    }
+
+    #[test]
+    fn polar() {
+        let kind = FnInputKind::from_str("r = 1 + cos(θ)").unwrap();
+        if let FnInputKind::Polar { radius, range } = kind {
+            assert_eq!(radius.eval(&Context::default(), &[("theta", 0.)]), 2.);
+            assert_eq!(range.end, 2. * core::f64::consts::PI);
+        } else {
+            panic!("Expected a polar curve, found {:?}", kind);
+        }
+        let kind = FnInputKind::from_str("r = theta, theta in [0, 4pi], samples = 20").unwrap();
+        assert!(matches!(kind, FnInputKind::Polar { range, .. }
+            if range.end == 4. * core::f64::consts::PI && range.samples == Some(20)));
+    }
+
+    #[test]
+    fn implicit() {
+        for s in &["x^2 + y^2 = 1", "y^2 = x^3 - x", "x*y - 1", "y = x*y"] {
+            let kind = FnInputKind::from_str(s).unwrap();
+            assert!(matches!(kind, FnInputKind::Implicit { .. }), "{}", s);
+        }
+        for s in &["y = sin(x)", "y = 2"] {
+            let kind = FnInputKind::from_str(s).unwrap();
+            assert!(matches!(kind, FnInputKind::Analytical { .. }), "{}", s);
+        }
+        assert!(FnInputKind::from_str("a = b").is_err());
+        let kind = FnInputKind::from_str("x^2 + y^2 = 1").unwrap();
+        if let FnInputKind::Implicit { expression } = kind {
+            let bindings = [("x", 1.), ("y", 0.)];
+            assert_eq!(expression.eval(&Context::default(), &bindings), 0.);
+        }
+    }
+
+    #[test]
+    fn three_dimensional() {
+        let kind = FnInputKind::from_str("z = x^2 - y^2").unwrap();
+        assert!(matches!(kind, FnInputKind::Surface { .. }));
+        let kind = FnInputKind::from_str("(cos(t), sin(t), t/5), t in [0, 4pi]").unwrap();
+        if let FnInputKind::Parametric3d { z, range, .. } = kind {
+            assert_eq!(z.eval(&Context::default(), &[("t", 5.)]), 1.);
+            assert_eq!(range.end, 4. * core::f64::consts::PI);
+        } else {
+            panic!("Expected a curve in space, found {:?}", kind);
+        }
+    }
+
+    #[test]
+    fn function_of_y() {
+        for s in &["x = 2", "x = y^2 - 1"] {
+            let kind = FnInputKind::from_str(s).unwrap();
+            assert!(matches!(kind, FnInputKind::FunctionOfY { .. }), "{}", s);
+        }
+        let kind = FnInputKind::from_str("x = x*y").unwrap();
+        assert!(matches!(kind, FnInputKind::Implicit { .. }));
+    }
+
+    #[test]
+    fn slope_field() {
+        let kind = FnInputKind::from_str("y' = x - y, [(0, 1), (1, max(2, 3))]");
+        assert!(kind.is_err());
+        let kind = FnInputKind::from_str("y' = sin(x * y), [(0, 1), (1, -2)]").unwrap();
+        match kind {
+            FnInputKind::SlopeField {
+                initial_conditions, ..
+            } => assert_eq!(initial_conditions, vec![(0., 1.), (1., -2.)]),
+            _ => panic!("{:?}", kind),
+        }
+        let s = slope_field::with_initial_condition("y' = x - y", (0.5, 1.)).unwrap();
+        assert_eq!(s, "y' = x - y, [(0.5, 1)]");
+        let s = slope_field::with_initial_condition(&s, (-1., 2.)).unwrap();
+        assert_eq!(s, "y' = x - y, [(0.5, 1), (-1, 2)]");
+    }
+
+    #[test]
+    fn initial_value_problem() {
+        let kind = FnInputKind::from_str("y'' = -y + a*y', y'(0) = 1, y(0) = 0").unwrap();
+        match &kind {
+            FnInputKind::InitialValueProblem {
+                x0, initial_values, ..
+            } => assert_eq!((*x0, initial_values.as_slice()), (0., &[0., 1.][..])),
+            _ => panic!("{:?}", kind),
+        }
+        assert_eq!(kind.free_variables(), vec!["a"]);
+        for s in &["y' = y'", "y'' = y, y(0) = 1", "y' = y, y(0) = 1, y(1) = 2"] {
+            assert!(FnInputKind::from_str(s).is_err(), "{}", s);
+        }
+
+        let mut fn_input = FnInput::default();
+        fn_input.set_kind(kind);
+        let key = SolutionKey {
+            domain: (0., 1.),
+            step: 0.1,
+            solver: Solver::default(),
+            parameters: vec![],
+            definitions: vec![],
+        };
+        assert_eq!(fn_input.solution(key.clone(), || vec![(0., 0.)]).len(), 1);
+        assert_eq!(fn_input.solution(key.clone(), Vec::new).len(), 1);
+        let other = SolutionKey { step: 0.2, ..key };
+        assert!(fn_input.solution(other, Vec::new).is_empty());
+    }
+
+    #[test]
+    fn inequality() {
+        for s in &["y < sin(x)", "x^2 + y^2 <= 4", "y > x && y < 2x"] {
+            let kind = FnInputKind::from_str(s).unwrap();
+            assert!(matches!(kind, FnInputKind::Inequality { .. }), "{}", s);
+        }
+        assert!(FnInputKind::from_str("y == x").is_err());
+    }
+
+    #[test]
+    fn points() {
+        let kind = FnInputKind::from_str("[(0, 1), (1, 2), (2, 0)], step-right").unwrap();
+        let stored = ron::ser::to_string(&kind).unwrap();
+        let restored: FnInputKind = ron::de::from_str(&stored).unwrap();
+        assert!(matches!(restored, FnInputKind::Points { interpolation, .. }
+            if interpolation == Interpolation::StepRight));
+        assert_eq!(restored.eval(&Context::default(), 0.5), 2.);
+    }
+
+    #[test]
+    fn definition() {
+        let (name, kind) = parse_definition("g(x) = f(x)^2 + 1").unwrap();
+        assert_eq!(name.as_deref(), Some("g"));
+        assert_eq!(kind.calls(), vec!["f".to_string()]);
+        let (name, _) = parse_definition("data(x) = [(0, 1), (1, 2)]").unwrap();
+        assert_eq!(name.as_deref(), Some("data"));
+        let (name, kind) = parse_definition("sin(x) = y").unwrap();
+        assert_eq!(name, None);
+        assert!(matches!(kind, FnInputKind::Implicit { .. }));
+    }
 }
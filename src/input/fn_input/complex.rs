@@ -0,0 +1,188 @@
+use super::{
+    expression::{is_constant, variables},
+    Context, FormatError,
+};
+use core::convert::TryFrom;
+use core::fmt::Debug;
+use core::str::FromStr;
+use exmex::{BinOp, Operator, OwnedFlatEx};
+use num_complex::Complex;
+use serde::{Deserialize, Serialize};
+
+/// Numbers, possibly imaginary as in `2.5i`, and the imaginary unit `i` on its own.
+const NUMBER_PATTERN: &str = r"[0-9]+(\.[0-9]*)?i?|\.[0-9]+i?|i\b";
+
+/// Expression of a complex variable `z`, such as `(z^2 - 1)/(z^2 + 2i)`.
+///
+/// Other variables are real parameters, whose values are given by a [`Context`].
+/// It is (de)serialized through the text it was parsed from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ComplexExpression {
+    text: String,
+    flat: OwnedFlatEx<Value>,
+    /// Variables of `flat`, in the order `exmex` expects their values.
+    variables: Vec<String>,
+}
+
+impl ComplexExpression {
+    pub fn eval(&self, context: &Context, z: Complex<f64>) -> Complex<f64> {
+        let values: Vec<Value> = self
+            .variables
+            .iter()
+            .map(|name| match name.as_str() {
+                "z" => Value(z),
+                _ => Value(Complex::from(context.parameter(name))),
+            })
+            .collect();
+        self.flat
+            .eval(&values)
+            .map_or(Complex::new(f64::NAN, f64::NAN), |value| value.0)
+    }
+
+    /// Names of the variables of the expression, including `z` but not the constants.
+    pub fn variables(&self) -> Vec<String> {
+        self.variables
+            .iter()
+            .filter(|name| !is_constant(name))
+            .cloned()
+            .collect()
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+impl Default for ComplexExpression {
+    fn default() -> Self {
+        "z".parse().unwrap()
+    }
+}
+
+impl FromStr for ComplexExpression {
+    type Err = FormatError;
+    fn from_str(s: &str) -> Result<Self, FormatError> {
+        let operators = operators();
+        let flat = exmex::parse_with_number_pattern(s, &operators, NUMBER_PATTERN)
+            .map_err(|_| FormatError(s.to_string()))?;
+        let flat = OwnedFlatEx::from_flatex(flat);
+        let variables = variables(&flat);
+        Ok(ComplexExpression {
+            text: s.trim().to_string(),
+            flat,
+            variables,
+        })
+    }
+}
+
+impl TryFrom<String> for ComplexExpression {
+    type Error = FormatError;
+    fn try_from(s: String) -> Result<Self, FormatError> {
+        s.parse()
+    }
+}
+
+impl From<ComplexExpression> for String {
+    fn from(expression: ComplexExpression) -> Self {
+        expression.text
+    }
+}
+
+/// Complex number as handled by `exmex`.
+///
+/// Its debug representation, used when unparsing, is free of curly braces,
+/// which `exmex` reserves for variables.
+#[derive(Clone, Copy, PartialEq)]
+struct Value(Complex<f64>);
+
+impl Debug for Value {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        write!(f, "({})", self.0)
+    }
+}
+
+impl FromStr for Value {
+    type Err = FormatError;
+    fn from_str(s: &str) -> Result<Self, FormatError> {
+        s.parse().map(Value).map_err(|_| FormatError(s.to_string()))
+    }
+}
+
+/// Arithmetic and elementary functions of complex numbers,
+/// with the same priorities as the default operators of `exmex`.
+fn operators<'a>() -> Vec<Operator<'a, Value>> {
+    fn binary(repr: &str, apply: fn(Value, Value) -> Value, prio: i32) -> Operator<'_, Value> {
+        Operator {
+            repr,
+            bin_op: Some(BinOp { apply, prio }),
+            unary_op: None,
+        }
+    }
+    fn unary(repr: &str, apply: fn(Value) -> Value) -> Operator<'_, Value> {
+        Operator {
+            repr,
+            bin_op: None,
+            unary_op: Some(apply),
+        }
+    }
+    vec![
+        binary("^", |a, b| Value(a.0.powc(b.0)), 2),
+        binary("*", |a, b| Value(a.0 * b.0), 1),
+        binary("/", |a, b| Value(a.0 / b.0), 1),
+        Operator {
+            repr: "+",
+            bin_op: Some(BinOp {
+                apply: |a, b| Value(a.0 + b.0),
+                prio: 0,
+            }),
+            unary_op: Some(|a| a),
+        },
+        Operator {
+            repr: "-",
+            bin_op: Some(BinOp {
+                apply: |a, b| Value(a.0 - b.0),
+                prio: 0,
+            }),
+            unary_op: Some(|a| Value(-a.0)),
+        },
+        unary("sin", |a| Value(a.0.sin())),
+        unary("cos", |a| Value(a.0.cos())),
+        unary("tan", |a| Value(a.0.tan())),
+        unary("asin", |a| Value(a.0.asin())),
+        unary("acos", |a| Value(a.0.acos())),
+        unary("atan", |a| Value(a.0.atan())),
+        unary("sinh", |a| Value(a.0.sinh())),
+        unary("cosh", |a| Value(a.0.cosh())),
+        unary("tanh", |a| Value(a.0.tanh())),
+        unary("exp", |a| Value(a.0.exp())),
+        unary("log", |a| Value(a.0.ln())),
+        unary("sqrt", |a| Value(a.0.sqrt())),
+        unary("conj", |a| Value(a.0.conj())),
+        unary("abs", |a| Value(Complex::from(a.0.norm()))),
+        unary("arg", |a| Value(Complex::from(a.0.arg()))),
+        unary("re", |a| Value(Complex::from(a.0.re))),
+        unary("im", |a| Value(Complex::from(a.0.im))),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rational() {
+        let expression: ComplexExpression = "(z^2 - 1)/(z^2 + 2i)".parse().unwrap();
+        let context = Context::default();
+        let value = expression.eval(&context, Complex::new(1., 0.));
+        assert!(value.norm() < 1e-12);
+        let value = expression.eval(&context, Complex::new(0., 0.));
+        assert!((value - Complex::new(0., 0.5)).norm() < 1e-12);
+
+        let expression: ComplexExpression = "a*exp(i*z) + im(z)".parse().unwrap();
+        let mut context = Context::default();
+        context.insert_parameter("a", 2.);
+        let value = expression.eval(&context, Complex::new(0., 1.));
+        assert!((value - Complex::new(2. / core::f64::consts::E + 1., 0.)).norm() < 1e-12);
+    }
+}
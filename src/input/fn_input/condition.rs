@@ -0,0 +1,196 @@
+use super::{split_top_level, Expression, FormatError};
+use core::str::FromStr;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Relation {
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Equal,
+    NotEqual,
+}
+
+impl Relation {
+    /// Textual representations, two-character ones first so that `<=` is not read as `<`.
+    const REPRS: [(&'static str, Relation); 6] = [
+        ("<=", Relation::LessEqual),
+        (">=", Relation::GreaterEqual),
+        ("==", Relation::Equal),
+        ("!=", Relation::NotEqual),
+        ("<", Relation::Less),
+        (">", Relation::Greater),
+    ];
+
+    /// Whether the relation compares sizes, so that it defines a region of the plane.
+    pub fn is_order(self) -> bool {
+        matches!(
+            self,
+            Relation::Less | Relation::LessEqual | Relation::Greater | Relation::GreaterEqual
+        )
+    }
+
+    /// Whether points where both sides are equal do not satisfy the relation.
+    pub fn is_strict(self) -> bool {
+        matches!(
+            self,
+            Relation::Less | Relation::Greater | Relation::NotEqual
+        )
+    }
+
+    pub fn holds(self, lhs: f64, rhs: f64) -> bool {
+        match self {
+            Relation::Less => lhs < rhs,
+            Relation::LessEqual => lhs <= rhs,
+            Relation::Greater => lhs > rhs,
+            Relation::GreaterEqual => lhs >= rhs,
+            Relation::Equal => lhs == rhs,
+            Relation::NotEqual => lhs != rhs,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Comparison {
+    pub lhs: Expression,
+    pub relation: Relation,
+    pub rhs: Expression,
+}
+
+/// Comparisons combined with `&&` and `||`, where `&&` binds stronger.
+///
+/// Chained comparisons such as `0 < x <= 1` are also accepted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Condition {
+    /// Disjunction of conjunctions.
+    pub clauses: Vec<Vec<Comparison>>,
+}
+
+impl Condition {
+    /// Whether the condition holds, evaluating each side of the comparisons with `eval`.
+    pub fn holds<F>(&self, eval: F) -> bool
+    where
+        F: Fn(&Expression) -> f64,
+    {
+        self.clauses.iter().any(|clause| {
+            clause.iter().all(|comparison| {
+                comparison
+                    .relation
+                    .holds(eval(&comparison.lhs), eval(&comparison.rhs))
+            })
+        })
+    }
+
+    /// Condition that holds where both `self` and `other` hold.
+    pub fn and(&self, other: &Condition) -> Condition {
+        let clauses =
+            self.clauses
+                .iter()
+                .flat_map(|clause| {
+                    other.clauses.iter().map(move |other_clause| {
+                        clause.iter().chain(other_clause).cloned().collect()
+                    })
+                })
+                .collect();
+        Condition { clauses }
+    }
+
+    pub fn comparisons(&self) -> impl Iterator<Item = &Comparison> {
+        self.clauses.iter().flatten()
+    }
+
+    /// Both sides of all the comparisons.
+    pub fn expressions(&self) -> Vec<&Expression> {
+        self.clauses
+            .iter()
+            .flatten()
+            .flat_map(|comparison| vec![&comparison.lhs, &comparison.rhs])
+            .collect()
+    }
+}
+
+impl FromStr for Condition {
+    type Err = FormatError;
+    fn from_str(s: &str) -> Result<Self, FormatError> {
+        let error = || FormatError(s.to_string());
+        let clauses = split_top_level(s, "||")
+            .into_iter()
+            .map(|clause| {
+                let comparisons = split_top_level(clause, "&&")
+                    .into_iter()
+                    .map(parse_chain)
+                    .collect::<Option<Vec<Vec<Comparison>>>>()?;
+                Some(comparisons.into_iter().flatten().collect())
+            })
+            .collect::<Option<Vec<Vec<Comparison>>>>()
+            .ok_or_else(error)?;
+        Ok(Condition { clauses })
+    }
+}
+
+/// Parses a chain of comparisons like `a < b <= c` into `a < b && b <= c`.
+fn parse_chain(s: &str) -> Option<Vec<Comparison>> {
+    let mut terms = Vec::new();
+    let mut relations = Vec::new();
+    let mut depth = 0i32;
+    let mut last = 0;
+    let mut i = 0;
+    while i < s.len() {
+        let rest = &s[i..];
+        match rest.chars().next()? {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            _ if depth == 0 => {
+                if let Some((repr, relation)) = Relation::REPRS
+                    .iter()
+                    .find(|(repr, _)| rest.starts_with(repr))
+                {
+                    terms.push(&s[last..i]);
+                    relations.push(*relation);
+                    i += repr.len();
+                    last = i;
+                    continue;
+                }
+            }
+            _ => (),
+        }
+        i += rest.chars().next()?.len_utf8();
+    }
+    terms.push(&s[last..]);
+    if relations.is_empty() {
+        return None;
+    }
+
+    let expressions = terms
+        .into_iter()
+        .map(|term| term.parse().ok())
+        .collect::<Option<Vec<Expression>>>()?;
+    Some(
+        relations
+            .into_iter()
+            .zip(expressions.windows(2))
+            .map(|(relation, sides)| Comparison {
+                lhs: sides[0].clone(),
+                relation,
+                rhs: sides[1].clone(),
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::Context;
+
+    #[test]
+    fn chained() {
+        let condition = Condition::from_str("0 < x <= 1 || x == -1").unwrap();
+        let holds = |x: f64| condition.holds(|e| e.eval(&Context::default(), &[("x", x)]));
+        assert!(holds(1.));
+        assert!(holds(-1.));
+        assert!(!holds(0.));
+        assert!(!holds(2.));
+    }
+}
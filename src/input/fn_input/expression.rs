@@ -0,0 +1,349 @@
+use super::{FnInputKind, FormatError};
+use core::convert::TryFrom;
+use core::fmt::Debug;
+use core::str::FromStr;
+use exmex::OwnedFlatEx;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Prefix of the variables that stand for the result of a call to a named function.
+const CALL_PREFIX: char = '#';
+
+/// Constants known to every expression, since the operators of `exmex` include none.
+const CONSTANTS: [(&str, f64); 2] = [("pi", core::f64::consts::PI), ("e", core::f64::consts::E)];
+
+/// Mathematical expression that can call other named functions, as in `f(x)^2 + 1`.
+///
+/// Calls are replaced by variables before parsing the expression with `exmex`,
+/// and their values are computed at evaluation time through a [`Context`].
+/// It is (de)serialized through the text it was parsed from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Expression {
+    text: String,
+    flat: OwnedFlatEx<f64>,
+    /// Variables of `flat`, in the order `exmex` expects their values.
+    variables: Vec<String>,
+    calls: Vec<Call>,
+}
+
+#[derive(Debug, Clone)]
+struct Call {
+    name: String,
+    argument: Expression,
+}
+
+impl Expression {
+    /// Evaluates the expression, where `bindings` gives the value of some variables
+    /// and calls to named functions are resolved by `context`.
+    ///
+    /// Other variables take the value of the parameter with the same name in `context`.
+    /// Unknown parameters and calls to unknown functions result in `NaN`.
+    pub fn eval(&self, context: &Context, bindings: &[(&str, f64)]) -> f64 {
+        let values: Vec<f64> = self
+            .variables
+            .iter()
+            .map(|name| match call_index(name) {
+                Some(index) => {
+                    let call = &self.calls[index];
+                    context.call(&call.name, call.argument.eval(context, bindings))
+                }
+                None => bindings
+                    .iter()
+                    .find(|(variable, _)| variable == name)
+                    .map_or_else(|| context.parameter(name), |(_, value)| *value),
+            })
+            .collect();
+        self.flat.eval(&values).unwrap_or(f64::NAN)
+    }
+
+    /// Names of the variables of the expression, including those inside calls,
+    /// but not the constants.
+    pub fn variables(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .variables
+            .iter()
+            .filter(|name| call_index(name).is_none() && !is_constant(name))
+            .cloned()
+            .chain(self.calls.iter().flat_map(|call| call.argument.variables()))
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+
+    /// Text the expression was parsed from, or computed from for derivatives.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Derivative with respect to `variable`, computed symbolically by `exmex`.
+    ///
+    /// Returns `None` if the expression calls named functions, whose derivatives are unknown.
+    pub fn derivative(&self, variable: &str) -> Option<Expression> {
+        if !self.calls.is_empty() {
+            return None;
+        }
+        let index = match self.variables.iter().position(|name| name == variable) {
+            Some(index) => index,
+            None => return "0".parse().ok(),
+        };
+        // The derivative expects the values of the same variables as the expression.
+        let flat = self.flat.clone().partial(index).ok()?;
+        let text = flat.unparse().ok()?.replace(['{', '}'], "");
+        Some(Expression {
+            text,
+            flat,
+            variables: self.variables.clone(),
+            calls: vec![],
+        })
+    }
+
+    /// Whether `name` is one of the variables of the expression.
+    pub fn uses(&self, name: &str) -> bool {
+        self.variables().iter().any(|variable| variable == name)
+    }
+
+    /// Names of the functions called by the expression.
+    pub fn calls(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .calls
+            .iter()
+            .flat_map(|call| {
+                let mut names = call.argument.calls();
+                names.push(call.name.clone());
+                names
+            })
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+}
+
+impl FromStr for Expression {
+    type Err = FormatError;
+    fn from_str(s: &str) -> Result<Self, FormatError> {
+        let error = || FormatError(s.to_string());
+        let (rewritten, calls) = extract_calls(s).ok_or_else(error)?;
+        let flat = OwnedFlatEx::from_flatex(
+            exmex::parse_with_default_ops(&rewritten).map_err(|_| error())?,
+        );
+        let variables = variables(&flat);
+        Ok(Expression {
+            text: s.trim().to_string(),
+            flat,
+            variables,
+            calls,
+        })
+    }
+}
+
+impl TryFrom<String> for Expression {
+    type Error = FormatError;
+    fn try_from(s: String) -> Result<Self, FormatError> {
+        s.parse()
+    }
+}
+
+impl From<Expression> for String {
+    fn from(expression: Expression) -> Self {
+        expression.text
+    }
+}
+
+/// Replaces every call `name(argument)` to a function that is not an operator of `exmex`
+/// by the variable `{#i}`, where `i` is the index of the call, and makes the
+/// multiplication of a name by the number before it explicit.
+fn extract_calls(s: &str) -> Option<(String, Vec<Call>)> {
+    let operators = exmex::make_default_operators::<f64>();
+    let is_operator = |name: &str| operators.iter().any(|operator| operator.repr == name);
+
+    let mut rewritten = String::with_capacity(s.len());
+    let mut calls = Vec::new();
+    let mut chars = s.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if c == '{' {
+            // Variable in curly braces, kept as it is
+            let end = i + s[i..].find('}')?;
+            rewritten.push_str(&s[i..=end]);
+            while chars.peek().is_some_and(|(j, _)| *j <= end) {
+                chars.next();
+            }
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let mut end = i + c.len_utf8();
+            while let Some((j, c)) = chars.peek() {
+                if c.is_ascii_alphanumeric() || *c == '_' {
+                    end = j + c.len_utf8();
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            let name = &s[i..end];
+            // A number directly before a name multiplies it, as in `2x`, unless the
+            // name is the exponent of a number in scientific notation, as in `2e3`
+            let exponent = name.len() > 1
+                && name.starts_with(['e', 'E'])
+                && name[1..].bytes().all(|b| b.is_ascii_digit());
+            if rewritten.ends_with(|c: char| c.is_ascii_digit() || c == '.') && !exponent {
+                rewritten.push('*');
+            }
+            let rest = s[end..].trim_start();
+            if rest.starts_with('(') && !is_operator(name) {
+                let open = s.len() - rest.len();
+                let close = open + matching_parenthesis(&s[open..])?;
+                let argument = s[open + 1..close].parse().ok()?;
+                rewritten.push_str(&format!("{{{}{}}}", CALL_PREFIX, calls.len()));
+                calls.push(Call {
+                    name: name.to_string(),
+                    argument,
+                });
+                while chars.peek().is_some_and(|(j, _)| *j <= close) {
+                    chars.next();
+                }
+            } else {
+                rewritten.push_str(name);
+            }
+        } else {
+            rewritten.push(c);
+        }
+    }
+    Some((rewritten, calls))
+}
+
+/// Position of the parenthesis closing the one `s` starts with.
+fn matching_parenthesis(s: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => (),
+        }
+    }
+    None
+}
+
+/// Whether `name` is one of the [`CONSTANTS`].
+pub(super) fn is_constant(name: &str) -> bool {
+    CONSTANTS.iter().any(|(constant, _)| *constant == name)
+}
+
+fn call_index(variable: &str) -> Option<usize> {
+    variable.strip_prefix(CALL_PREFIX)?.parse().ok()
+}
+
+/// Names of the variables of `expression`, in the order `exmex` expects their values.
+pub(super) fn variables<T: Copy + Debug>(expression: &OwnedFlatEx<T>) -> Vec<String> {
+    let unparsed = expression.unparse().unwrap_or_default();
+    let mut names: Vec<String> = unparsed
+        .split('{')
+        .skip(1)
+        .filter_map(|rest| rest.split_once('}'))
+        .map(|(name, _)| name.to_string())
+        .collect();
+    names.sort_unstable();
+    names.dedup();
+    names
+}
+
+/// Named functions that expressions can call and values of the parameters,
+/// starting with the constants.
+#[derive(Debug)]
+pub struct Context<'a> {
+    functions: HashMap<&'a str, &'a FnInputKind>,
+    parameters: HashMap<&'a str, f64>,
+}
+
+impl Default for Context<'_> {
+    fn default() -> Self {
+        Context {
+            functions: HashMap::new(),
+            parameters: CONSTANTS.iter().copied().collect(),
+        }
+    }
+}
+
+impl<'a> Context<'a> {
+    /// Makes `kind` callable by `name`.
+    ///
+    /// The caller is responsible for not introducing circular definitions.
+    pub fn insert_function(&mut self, name: &'a str, kind: &'a FnInputKind) {
+        self.functions.insert(name, kind);
+    }
+
+    pub fn insert_parameter(&mut self, name: &'a str, value: f64) {
+        self.parameters.insert(name, value);
+    }
+
+    /// Value of the parameter `name`, or `NaN` if it is not known.
+    pub fn parameter(&self, name: &str) -> f64 {
+        self.parameters.get(name).copied().unwrap_or(f64::NAN)
+    }
+
+    /// Value of the function `name` at `x`, or `NaN` if it is not known.
+    pub fn call(&self, name: &str, x: f64) -> f64 {
+        self.functions
+            .get(name)
+            .map_or(f64::NAN, |kind| kind.eval(self, x))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calls() {
+        let square: FnInputKind = "x^2".parse().unwrap();
+        let mut context = Context::default();
+        context.insert_function("f", &square);
+
+        let expression: Expression = "f(f(x) + 1) - sin(x)".parse().unwrap();
+        assert_eq!(expression.calls(), vec!["f".to_string()]);
+        assert_eq!(expression.variables(), vec!["x".to_string()]);
+        assert_eq!(expression.eval(&context, &[("x", 0.)]), 1.);
+        assert!(expression.eval(&Context::default(), &[("x", 0.)]).is_nan());
+
+        let expression: Expression = "a * f(x)".parse().unwrap();
+        context.insert_parameter("a", 2.);
+        assert_eq!(expression.eval(&context, &[("x", 3.)]), 18.);
+    }
+
+    #[test]
+    fn coefficients() {
+        let square: FnInputKind = "x^2".parse().unwrap();
+        let mut context = Context::default();
+        context.insert_function("f", &square);
+
+        let expression: Expression = "2x + 0.5f(x) - 3sin(x)".parse().unwrap();
+        assert_eq!(expression.variables(), vec!["x".to_string()]);
+        assert_eq!(expression.text(), "2x + 0.5f(x) - 3sin(x)");
+        let value = expression.eval(&context, &[("x", 2.)]);
+        assert!((value - (4. + 2. - 3. * 2f64.sin())).abs() < 1e-12);
+        assert!("2pi".parse::<Expression>().unwrap().variables().is_empty());
+        assert!("x2 + y_1".parse::<Expression>().unwrap().uses("x2"));
+    }
+    #[test]
+    fn derivative() {
+        let context = Context::default();
+        let expression: Expression = "a * sin(x) + x^3".parse().unwrap();
+        let first = expression.derivative("x").unwrap();
+        let second = first.derivative("x").unwrap();
+        let bindings = [("x", 0.5), ("a", 2.)];
+        assert!((first.eval(&context, &bindings) - (2. * 0.5f64.cos() + 0.75)).abs() < 1e-12);
+        assert!((second.eval(&context, &bindings) - (-2. * 0.5f64.sin() + 3.)).abs() < 1e-12);
+        assert!(!first.text().contains('{'));
+
+        let constant: Expression = "a".parse().unwrap();
+        assert_eq!(constant.derivative("x").unwrap().eval(&context, &[]), 0.);
+        let call: Expression = "f(x)".parse().unwrap();
+        assert!(call.derivative("x").is_none());
+    }
+}
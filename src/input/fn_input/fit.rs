@@ -0,0 +1,326 @@
+use super::{Context, Expression};
+use crate::numerics::{levenberg_marquardt, linear_least_squares, polynomial_fit, r_squared};
+use core::str::FromStr;
+use serde::{Deserialize, Serialize};
+use yew::prelude::*;
+
+/// Highest degree of the polynomials fitted to point data.
+pub const MAX_FIT_DEGREE: usize = 10;
+
+/// Family of functions fitted to point data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum FitModel {
+    /// `a + b x`
+    #[default]
+    Linear,
+    /// `c_0 + c_1 x + ... + c_k x^k`
+    Polynomial,
+    /// `a exp(b x)`, fitted to the logarithm of positive data.
+    Exponential,
+    /// `a x^b`, fitted to the logarithms of positive data.
+    Power,
+    /// `a + b log(x)`, with the natural logarithm, fitted to data with positive `x`.
+    Logarithmic,
+    /// Expression written by the user, whose variables other than `x` are fitted
+    /// by the Levenberg-Marquardt method.
+    Custom,
+}
+
+impl FitModel {
+    pub const ALL: [FitModel; 6] = [
+        FitModel::Linear,
+        FitModel::Polynomial,
+        FitModel::Exponential,
+        FitModel::Power,
+        FitModel::Logarithmic,
+        FitModel::Custom,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FitModel::Linear => "linear",
+            FitModel::Polynomial => "polynomial",
+            FitModel::Exponential => "exponential",
+            FitModel::Power => "power",
+            FitModel::Logarithmic => "logarithmic",
+            FitModel::Custom => "custom",
+        }
+    }
+}
+
+/// Least-squares fit drawn along with point data.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Fit {
+    pub enabled: bool,
+    pub model: FitModel,
+    /// Degree of the polynomial model.
+    pub degree: usize,
+    /// Expression of the custom model, such as `a*exp(-b*x) + c`.
+    pub custom: String,
+}
+
+impl Default for Fit {
+    fn default() -> Self {
+        Fit {
+            enabled: false,
+            model: FitModel::default(),
+            degree: 2,
+            custom: "a*exp(-b*x) + c".to_string(),
+        }
+    }
+}
+
+/// Fitted function, with the quality of the fit.
+#[derive(Debug, Clone)]
+pub struct FitResult {
+    /// Fitted parameters, by name.
+    pub coefficients: Vec<(String, f64)>,
+    /// Fitted function, written as an analytical input.
+    pub text: String,
+    pub expression: Expression,
+    pub r_squared: f64,
+    /// Differences between the data and the fitted function.
+    pub residuals: Vec<f64>,
+}
+
+impl Fit {
+    /// Fits the model to `points`, unless the data does not suit the model
+    /// or the fit fails.
+    pub fn fit(&self, points: &[(f64, f64)], context: &Context) -> Option<FitResult> {
+        let (coefficients, text) = match self.model {
+            FitModel::Linear | FitModel::Polynomial => {
+                let degree = match self.model {
+                    FitModel::Linear => 1,
+                    _ => self.degree,
+                };
+                let coefficients = polynomial_fit(points, degree)?;
+                let text = coefficients
+                    .iter()
+                    .enumerate()
+                    .map(|(k, c)| match k {
+                        0 => number(*c),
+                        1 => format!("{}*x", number(*c)),
+                        _ => format!("{}*x^{}", number(*c), k),
+                    })
+                    .collect::<Vec<String>>()
+                    .join(" + ");
+                let names = (0..coefficients.len()).map(|k| format!("c_{}", k));
+                (names.zip(coefficients).collect(), text)
+            }
+            FitModel::Exponential => {
+                let logarithms = logarithms(points, false, true)?;
+                let (a, b) = line(&logarithms)?;
+                let a = a.exp();
+                let text = format!("{}*exp({}*x)", number(a), number(b));
+                (vec![("a".to_string(), a), ("b".to_string(), b)], text)
+            }
+            FitModel::Power => {
+                let logarithms = logarithms(points, true, true)?;
+                let (a, b) = line(&logarithms)?;
+                let a = a.exp();
+                let text = format!("{}*x^{}", number(a), number(b));
+                (vec![("a".to_string(), a), ("b".to_string(), b)], text)
+            }
+            FitModel::Logarithmic => {
+                let logarithms = logarithms(points, true, false)?;
+                let (a, b) = line(&logarithms)?;
+                let text = format!("{} + {}*log(x)", number(a), number(b));
+                (vec![("a".to_string(), a), ("b".to_string(), b)], text)
+            }
+            FitModel::Custom => {
+                let model = Expression::from_str(&self.custom).ok()?;
+                let names: Vec<String> = model
+                    .variables()
+                    .into_iter()
+                    .filter(|name| name != "x")
+                    .collect();
+                let evaluate = |parameters: &[f64], x: f64| {
+                    let mut bindings: Vec<(&str, f64)> = names
+                        .iter()
+                        .map(|name| name.as_str())
+                        .zip(parameters.iter().copied())
+                        .collect();
+                    bindings.push(("x", x));
+                    model.eval(context, &bindings)
+                };
+                let parameters = levenberg_marquardt(&evaluate, points, &vec![1.; names.len()])?;
+                let values: Vec<(&str, f64)> = names
+                    .iter()
+                    .map(|name| name.as_str())
+                    .zip(parameters.iter().copied())
+                    .collect();
+                let text = substitute(&self.custom, &values);
+                (names.into_iter().zip(parameters).collect(), text)
+            }
+        };
+        let expression = Expression::from_str(&text).ok()?;
+        let f = |x: f64| expression.eval(context, &[("x", x)]);
+        let residuals: Vec<f64> = points.iter().map(|(x, y)| y - f(*x)).collect();
+        if !residuals.iter().all(|residual| residual.is_finite()) {
+            return None;
+        }
+        Some(FitResult {
+            coefficients,
+            r_squared: r_squared(&f, points),
+            text,
+            expression,
+            residuals,
+        })
+    }
+}
+
+/// Intercept and slope of the line fitting `points` by least squares.
+fn line(points: &[(f64, f64)]) -> Option<(f64, f64)> {
+    let rows: Vec<Vec<f64>> = points.iter().map(|(x, _)| vec![1., *x]).collect();
+    let ys: Vec<f64> = points.iter().map(|(_, y)| *y).collect();
+    let coefficients = linear_least_squares(&rows, &ys)?;
+    Some((coefficients[0], coefficients[1]))
+}
+
+/// Points with the logarithm taken of their `x` or `y` coordinates, unless some
+/// of those are not positive.
+fn logarithms(points: &[(f64, f64)], x: bool, y: bool) -> Option<Vec<(f64, f64)>> {
+    let logarithm = |value: f64, take: bool| match take {
+        true if value > 0. => Some(value.ln()),
+        true => None,
+        false => Some(value),
+    };
+    points
+        .iter()
+        .map(|(u, v)| Some((logarithm(*u, x)?, logarithm(*v, y)?)))
+        .collect()
+}
+
+/// Number written so that it can be inserted in an expression.
+fn number(value: f64) -> String {
+    if value < 0. {
+        format!("({})", value)
+    } else {
+        value.to_string()
+    }
+}
+
+/// Replaces the variables of `text` by their `values`, leaving alone names that
+/// only contain them, such as functions.
+pub fn substitute(text: &str, values: &[(&str, f64)]) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        if !(c.is_alphabetic() || c == '_') {
+            result.push(c);
+            continue;
+        }
+        let mut end = start + c.len_utf8();
+        while let Some(&(i, c)) = chars.peek() {
+            if !(c.is_alphanumeric() || c == '_') {
+                break;
+            }
+            end = i + c.len_utf8();
+            chars.next();
+        }
+        let name = &text[start..end];
+        match values.iter().find(|(variable, _)| *variable == name) {
+            Some((_, value)) => result.push_str(&number(*value)),
+            None => result.push_str(name),
+        }
+    }
+    result
+}
+
+#[derive(Debug)]
+pub enum SetFit {
+    Enabled,
+    Model(ChangeData),
+    Degree(ChangeData),
+    Custom(ChangeData),
+}
+
+impl Fit {
+    pub fn update(&mut self, set: SetFit) -> ShouldRender {
+        match set {
+            SetFit::Enabled => {
+                self.enabled = !self.enabled;
+                true
+            }
+            SetFit::Model(data) => {
+                if let ChangeData::Select(element) = data {
+                    log::trace!("Trying to change fitted model to {}", element.value());
+                    if let Some(model) = FitModel::ALL
+                        .iter()
+                        .find(|model| model.name() == element.value())
+                    {
+                        self.model = *model;
+                    }
+                }
+                true
+            }
+            SetFit::Degree(data) => {
+                if let ChangeData::Value(x) = data {
+                    log::trace!("Trying to change degree of the fit to {}", x);
+                    if let Ok(proposal) = x.parse::<usize>() {
+                        self.degree = proposal.min(MAX_FIT_DEGREE);
+                    }
+                }
+                true
+            }
+            SetFit::Custom(data) => {
+                if let ChangeData::Value(s) = data {
+                    log::trace!("Trying to change custom model to {}", s);
+                    if Expression::from_str(&s).is_ok() {
+                        self.custom = s;
+                    }
+                }
+                true
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn models() {
+        let points: Vec<(f64, f64)> = (1..10)
+            .map(|i| (i as f64, 3. * (i as f64).powf(-0.5)))
+            .collect();
+        let context = Context::default();
+        let fit = Fit {
+            enabled: true,
+            model: FitModel::Power,
+            ..Fit::default()
+        };
+        let result = fit.fit(&points, &context).unwrap();
+        assert!((result.coefficients[0].1 - 3.).abs() < 1e-9);
+        assert!((result.coefficients[1].1 + 0.5).abs() < 1e-9);
+        assert!((result.r_squared - 1.).abs() < 1e-9);
+        assert!(result
+            .residuals
+            .iter()
+            .all(|residual| residual.abs() < 1e-9));
+
+        let custom = Fit {
+            model: FitModel::Custom,
+            custom: "a*sqrt(x) + b".to_string(),
+            ..fit
+        };
+        let result = custom
+            .fit(&[(0., 1.), (1., 3.), (4., 5.)], &context)
+            .unwrap();
+        assert!((result.expression.eval(&context, &[("x", 9.)]) - 7.).abs() < 1e-6);
+
+        let logarithmic = Fit {
+            model: FitModel::Logarithmic,
+            ..Fit::default()
+        };
+        assert!(logarithmic.fit(&[(-1., 0.), (1., 1.)], &context).is_none());
+        let result = logarithmic.fit(&[(1., 2.), (2., 1.)], &context).unwrap();
+        assert!((result.expression.eval(&context, &[("x", 4.)])).abs() < 1e-9);
+        assert_eq!(
+            substitute("a*abs(x) + ab", &[("a", -2.), ("ab", 1.)]),
+            "(-2)*abs(x) + 1"
+        );
+    }
+}
@@ -0,0 +1,97 @@
+use super::{parse_constant, split_top_level, Context, Expression, Solver};
+use crate::numerics::solve_system;
+
+/// Names under which `y` and its derivatives are bound in the equation,
+/// `y'` being written `y_1` and so on.
+pub const DERIVATIVES: [&str; 4] = ["y", "y_1", "y_2", "y_3"];
+
+/// Parses `y^(n) = f(x, y, y', ...)` written with primes, followed by the values of `y`
+/// and its derivatives up to the order `n - 1` at the same point, as in
+/// `y'' = -y, y(0) = 0, y'(0) = 1`.
+///
+/// Returns the equation, the initial point and the initial values.
+pub fn parse_initial_value_problem(s: &str) -> Option<(Expression, f64, Vec<f64>)> {
+    let parts = split_top_level(s, ",");
+    let (lhs, rhs) = parts[0].split_once('=')?;
+    let order = count_primes(lhs.trim().strip_prefix('y')?)?;
+    if !(1..=DERIVATIVES.len()).contains(&order) || parts.len() != order + 1 {
+        return None;
+    }
+    let expression: Expression = with_derivative_names(rhs).parse().ok()?;
+    if DERIVATIVES[order..]
+        .iter()
+        .any(|name| expression.uses(name))
+    {
+        return None;
+    }
+
+    let mut x0 = None;
+    let mut initial_values = vec![None; order];
+    for condition in &parts[1..] {
+        let (lhs, value) = condition.split_once('=')?;
+        let lhs = lhs.trim().strip_prefix('y')?;
+        let derivative = lhs.chars().take_while(|c| *c == '\'').count();
+        let point = lhs[derivative..]
+            .trim()
+            .strip_prefix('(')?
+            .strip_suffix(')')?;
+        let point = parse_constant(point)?;
+        if *x0.get_or_insert(point) != point {
+            return None;
+        }
+        match initial_values.get_mut(derivative) {
+            Some(initial_value @ None) => *initial_value = Some(parse_constant(value)?),
+            _ => return None,
+        }
+    }
+    let initial_values = initial_values.into_iter().collect::<Option<Vec<f64>>>()?;
+    Some((expression, x0?, initial_values))
+}
+
+/// Number of primes making up `s`, if it is made only of primes.
+fn count_primes(s: &str) -> Option<usize> {
+    if s.chars().all(|c| c == '\'') {
+        Some(s.len())
+    } else {
+        None
+    }
+}
+
+/// Replaces the derivatives of `y` written with primes by their names.
+fn with_derivative_names(s: &str) -> String {
+    let mut s = s.to_string();
+    for (order, name) in DERIVATIVES.iter().enumerate().skip(1).rev() {
+        s = s.replace(&format!("y{}", "'".repeat(order)), name);
+    }
+    s
+}
+
+/// Points of the solution over `domain`, where the equation is turned into a first
+/// order system of `y` and its derivatives.
+pub fn solve(
+    expression: &Expression,
+    context: &Context,
+    (x0, initial_values): (f64, &[f64]),
+    domain: (f64, f64),
+    step: f64,
+    solver: Solver,
+) -> Vec<(f64, f64)> {
+    let f = |x: f64, y: &[f64]| {
+        let mut bindings = vec![("x", x)];
+        bindings.extend(DERIVATIVES.iter().copied().zip(y.iter().copied()));
+        let mut derivatives = y[1..].to_vec();
+        derivatives.push(expression.eval(context, &bindings));
+        derivatives
+    };
+    solve_system(
+        f,
+        (x0, initial_values),
+        domain,
+        solver.method,
+        step,
+        solver.tolerance,
+    )
+    .into_iter()
+    .map(|(x, y)| (x, y[0]))
+    .collect()
+}
@@ -0,0 +1,93 @@
+use crate::numerics::Quadrature;
+use serde::{Deserialize, Serialize};
+use yew::prelude::*;
+
+/// Integrals of a function of `x` drawn along with it.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Integral {
+    /// Whether the antiderivative `F(x) = ∫_a^x f` is drawn.
+    pub antiderivative: bool,
+    /// Lower bound `a` of the antiderivative.
+    pub lower_bound: f64,
+    pub quadrature: Quadrature,
+    /// Whether the area under the curve between `bounds` is shaded and its value shown.
+    pub area: bool,
+    pub bounds: (f64, f64),
+}
+
+impl Default for Integral {
+    fn default() -> Self {
+        Integral {
+            antiderivative: false,
+            lower_bound: 0.,
+            quadrature: Quadrature::default(),
+            area: false,
+            bounds: (0., 1.),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SetIntegral {
+    Antiderivative,
+    LowerBound(ChangeData),
+    Quadrature(ChangeData),
+    Area,
+    Start(ChangeData),
+    End(ChangeData),
+}
+
+impl Integral {
+    pub fn update(&mut self, set: SetIntegral) -> ShouldRender {
+        match set {
+            SetIntegral::Antiderivative => {
+                self.antiderivative = !self.antiderivative;
+                true
+            }
+            SetIntegral::LowerBound(data) => {
+                if let ChangeData::Value(x) = data {
+                    log::trace!("Trying to change lower bound to {}", x);
+                    if let Ok(proposal) = x.parse::<f64>() {
+                        self.lower_bound = proposal;
+                    }
+                }
+                true
+            }
+            SetIntegral::Quadrature(data) => {
+                if let ChangeData::Select(element) = data {
+                    log::trace!("Trying to change quadrature to {}", element.value());
+                    if let Some(quadrature) = Quadrature::ALL
+                        .iter()
+                        .find(|quadrature| quadrature.name() == element.value())
+                    {
+                        self.quadrature = *quadrature;
+                    }
+                }
+                true
+            }
+            SetIntegral::Area => {
+                self.area = !self.area;
+                true
+            }
+            SetIntegral::Start(data) => {
+                if let ChangeData::Value(x) = data {
+                    log::trace!("Trying to change start of the area to {}", x);
+                    if let Ok(proposal) = x.parse::<f64>() {
+                        self.bounds.0 = proposal;
+                    }
+                }
+                true
+            }
+            SetIntegral::End(data) => {
+                if let ChangeData::Value(x) = data {
+                    log::trace!("Trying to change end of the area to {}", x);
+                    if let Ok(proposal) = x.parse::<f64>() {
+                        self.bounds.1 = proposal;
+                    }
+                }
+                true
+            }
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use super::{split_top_level, Condition, Context, Expression};
+use serde::{Deserialize, Serialize};
+
+/// Piece of a piecewise function: `expression` is used where `condition` holds,
+/// or everywhere if there is no condition.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Branch {
+    pub condition: Option<Condition>,
+    pub expression: Expression,
+}
+
+impl Branch {
+    /// The expression of the branch and those of its condition.
+    pub fn expressions(&self) -> Vec<&Expression> {
+        let mut expressions = vec![&self.expression];
+        if let Some(condition) = &self.condition {
+            expressions.extend(condition.expressions());
+        }
+        expressions
+    }
+}
+
+/// Parses either `condition ? expression : otherwise`, where `otherwise` can be
+/// another conditional, or `piecewise(condition: expression; ...; otherwise)`,
+/// where the last branch without condition is optional.
+pub fn parse_branches(s: &str) -> Option<Vec<Branch>> {
+    let s = s.trim();
+    let branches = match s
+        .strip_prefix("piecewise")
+        .and_then(|rest| rest.trim_start().strip_prefix('('))
+        .and_then(|rest| rest.strip_suffix(')'))
+    {
+        Some(inner) => split_top_level(inner, ";")
+            .into_iter()
+            .map(|piece| match piece.split_once(':') {
+                Some((condition, expression)) => Some(Branch {
+                    condition: Some(condition.parse().ok()?),
+                    expression: parse_expression(expression)?,
+                }),
+                None => Some(Branch {
+                    condition: None,
+                    expression: parse_expression(piece)?,
+                }),
+            })
+            .collect::<Option<Vec<Branch>>>()?,
+        None => {
+            if split_top_level(s, "?").len() < 2 {
+                return None;
+            }
+            parse_ternary(s)?
+        }
+    };
+    if branches
+        .iter()
+        .flat_map(Branch::expressions)
+        .any(|expression| expression.uses("y"))
+    {
+        None
+    } else {
+        Some(branches)
+    }
+}
+
+fn parse_ternary(s: &str) -> Option<Vec<Branch>> {
+    let parts = split_top_level(s, "?");
+    if parts.len() == 1 {
+        return Some(vec![Branch {
+            condition: None,
+            expression: parse_expression(s)?,
+        }]);
+    }
+    let condition: Condition = parts[0].parse().ok()?;
+    let rest = &s[parts[0].len() + 1..];
+    let (then, otherwise) = split_branches(rest)?;
+    // A conditional in the first branch only applies where the condition holds.
+    let mut branches: Vec<Branch> = parse_ternary(then)?
+        .into_iter()
+        .map(|branch| Branch {
+            condition: Some(match &branch.condition {
+                Some(inner) => condition.and(inner),
+                None => condition.clone(),
+            }),
+            expression: branch.expression,
+        })
+        .collect();
+    branches.extend(parse_ternary(otherwise)?);
+    Some(branches)
+}
+
+/// Splits `then : otherwise` at the top-level colon matching the question mark
+/// before it, so that conditionals nested in `then` are left whole.
+fn split_branches(s: &str) -> Option<(&str, &str)> {
+    let mut nested = 0;
+    let mut end = 0;
+    for (i, piece) in split_top_level(s, ":").into_iter().enumerate() {
+        nested += split_top_level(piece, "?").len() - 1;
+        if nested == i {
+            return Some((&s[..end + piece.len()], s.get(end + piece.len() + 1..)?));
+        }
+        end += piece.len() + 1;
+    }
+    None
+}
+
+fn parse_expression(s: &str) -> Option<Expression> {
+    s.parse().ok()
+}
+
+/// Index of the first branch that applies at `x`.
+fn branch_at(branches: &[Branch], context: &Context, x: f64) -> Option<usize> {
+    branches.iter().position(|branch| match &branch.condition {
+        Some(condition) => condition.holds(|e| e.eval(context, &[("x", x)])),
+        None => true,
+    })
+}
+
+/// Value at `x` of the first branch that applies, or `NaN` if none does.
+pub fn eval(branches: &[Branch], context: &Context, x: f64) -> f64 {
+    branch_at(branches, context, x).map_or(f64::NAN, |index| {
+        branches[index].expression.eval(context, &[("x", x)])
+    })
+}
+
+/// Samples the piecewise function over `grid`.
+///
+/// Where the applicable branch changes, the boundary is located by bisection.
+/// If the function jumps there, a non-finite point is inserted so that no
+/// vertical connector is drawn. Points where no branch applies are not finite.
+pub fn sample<I>(branches: &[Branch], context: &Context, grid: I) -> Vec<(f64, f64)>
+where
+    I: Iterator<Item = f64>,
+{
+    let eval = |index: usize, x: f64| branches[index].expression.eval(context, &[("x", x)]);
+    let mut points = Vec::new();
+    let mut previous: Option<(f64, Option<usize>)> = None;
+    for x in grid {
+        let current = branch_at(branches, context, x);
+        if let Some((previous_x, previous_branch)) = previous {
+            if previous_branch != current {
+                let (mut left, mut right) = (previous_x, x);
+                for _ in 0..60 {
+                    let middle = (left + right) / 2.;
+                    if branch_at(branches, context, middle) == previous_branch {
+                        left = middle;
+                    } else {
+                        right = middle;
+                    }
+                }
+                let left_value = previous_branch.map(|index| eval(index, left));
+                let right_value = current.map(|index| eval(index, right));
+                match (left_value, right_value) {
+                    (Some(l), Some(r)) if (l - r).abs() <= 1e-6 * (1. + l.abs().max(r.abs())) => {
+                        points.push((right, r));
+                    }
+                    _ => {
+                        if let Some(l) = left_value {
+                            points.push((left, l));
+                        }
+                        points.push((f64::NAN, f64::NAN));
+                        if let Some(r) = right_value {
+                            points.push((right, r));
+                        }
+                    }
+                }
+            }
+        }
+        points.push((x, current.map_or(f64::NAN, |index| eval(index, x))));
+        previous = Some((x, current));
+    }
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jump() {
+        let branches = parse_branches("x < 0 ? -1 : x < 1 ? x : 1").unwrap();
+        assert_eq!(branches.len(), 3);
+        let grid = itertools_num::linspace(-1., 2., 4);
+        let points = sample(&branches, &Context::default(), grid);
+        // The jump at zero is cut, the continuous join at one is not.
+        assert_eq!(points.iter().filter(|(x, _)| x.is_nan()).count(), 1);
+
+        let branches = parse_branches("x < 1 ? x < 0 ? -1 : 0 : 1").unwrap();
+        let values: Vec<f64> = [-1., 0.5, 2.]
+            .iter()
+            .map(|x| eval(&branches, &Context::default(), *x))
+            .collect();
+        assert_eq!(values, vec![-1., 0., 1.]);
+
+        let branches = parse_branches("piecewise(x<0: -x; x>=0: x^2)").unwrap();
+        assert_eq!(branches.len(), 2);
+        assert!(parse_branches("piecewise(y<0: -x; x^2)").is_none());
+    }
+}
@@ -0,0 +1,131 @@
+use serde::{Deserialize, Serialize};
+use yew::prelude::*;
+
+/// How point data is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DisplayMode {
+    /// Only the interpolated curve.
+    #[default]
+    Line,
+    /// Only the points themselves, without implying a curve through them.
+    Markers,
+    MarkersAndLine,
+}
+
+impl DisplayMode {
+    pub const ALL: [DisplayMode; 3] = [
+        DisplayMode::Line,
+        DisplayMode::Markers,
+        DisplayMode::MarkersAndLine,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DisplayMode::Line => "line",
+            DisplayMode::Markers => "markers",
+            DisplayMode::MarkersAndLine => "markers and line",
+        }
+    }
+
+    pub fn has_line(&self) -> bool {
+        matches!(self, DisplayMode::Line | DisplayMode::MarkersAndLine)
+    }
+
+    pub fn has_markers(&self) -> bool {
+        matches!(self, DisplayMode::Markers | DisplayMode::MarkersAndLine)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum MarkerShape {
+    #[default]
+    Circle,
+    Cross,
+    Triangle,
+    Square,
+}
+
+impl MarkerShape {
+    pub const ALL: [MarkerShape; 4] = [
+        MarkerShape::Circle,
+        MarkerShape::Cross,
+        MarkerShape::Triangle,
+        MarkerShape::Square,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MarkerShape::Circle => "circle",
+            MarkerShape::Cross => "cross",
+            MarkerShape::Triangle => "triangle",
+            MarkerShape::Square => "square",
+        }
+    }
+}
+
+/// Appearance of point data in the plot.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PointStyle {
+    pub mode: DisplayMode,
+    pub shape: MarkerShape,
+    /// Size of the markers, in pixels.
+    pub size: u32,
+}
+
+impl Default for PointStyle {
+    fn default() -> Self {
+        PointStyle {
+            mode: DisplayMode::default(),
+            shape: MarkerShape::default(),
+            size: 3,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SetPointStyle {
+    Mode(ChangeData),
+    Shape(ChangeData),
+    Size(ChangeData),
+}
+
+impl PointStyle {
+    pub fn update(&mut self, set: SetPointStyle) -> ShouldRender {
+        match set {
+            SetPointStyle::Mode(data) => {
+                if let ChangeData::Select(element) = data {
+                    log::trace!("Trying to change display mode to {}", element.value());
+                    if let Some(mode) = DisplayMode::ALL
+                        .iter()
+                        .find(|mode| mode.name() == element.value())
+                    {
+                        self.mode = *mode;
+                    }
+                }
+                true
+            }
+            SetPointStyle::Shape(data) => {
+                if let ChangeData::Select(element) = data {
+                    log::trace!("Trying to change marker shape to {}", element.value());
+                    if let Some(shape) = MarkerShape::ALL
+                        .iter()
+                        .find(|shape| shape.name() == element.value())
+                    {
+                        self.shape = *shape;
+                    }
+                }
+                true
+            }
+            SetPointStyle::Size(data) => {
+                if let ChangeData::Value(x) = data {
+                    log::trace!("Trying to change marker size to {}", x);
+                    if let Ok(proposal) = x.parse::<u32>() {
+                        self.size = proposal;
+                    }
+                }
+                true
+            }
+        }
+    }
+}
@@ -0,0 +1,220 @@
+use super::{split_top_level, FormatError};
+use core::fmt::Display;
+use core::iter::FromIterator;
+use core::str::FromStr;
+use serde::{Deserialize, Serialize};
+use splines::{key::Key, spline::Spline};
+
+/// How the values between two consecutive points are computed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Interpolation {
+    Linear,
+    /// The value of the point on the left is kept until the next point.
+    StepLeft,
+    /// The value of the point on the right is used from the previous point on.
+    StepRight,
+    CatmullRom,
+    #[default]
+    Cosine,
+    Bezier,
+}
+
+impl Interpolation {
+    pub const ALL: [Interpolation; 6] = [
+        Interpolation::Linear,
+        Interpolation::StepLeft,
+        Interpolation::StepRight,
+        Interpolation::CatmullRom,
+        Interpolation::Cosine,
+        Interpolation::Bezier,
+    ];
+
+    /// Name used in the input syntax.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Interpolation::Linear => "linear",
+            Interpolation::StepLeft => "step-left",
+            Interpolation::StepRight => "step-right",
+            Interpolation::CatmullRom => "catmull-rom",
+            Interpolation::Cosine => "cosine",
+            Interpolation::Bezier => "bezier",
+        }
+    }
+}
+
+impl Display for Interpolation {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Interpolation {
+    type Err = FormatError;
+    fn from_str(s: &str) -> Result<Self, FormatError> {
+        Interpolation::ALL
+            .iter()
+            .find(|interpolation| interpolation.name() == s.trim())
+            .copied()
+            .ok_or_else(|| FormatError(s.to_string()))
+    }
+}
+
+/// Parses `[(x, y), ...]`, optionally followed by `, interpolation`.
+pub fn parse_points(s: &str) -> Option<(Vec<(f64, f64)>, Interpolation)> {
+    let parts = split_top_level(s, ",");
+    let (points, interpolation) = match parts.as_slice() {
+        [points] => (points, Interpolation::default()),
+        [points, interpolation] => (points, interpolation.parse().ok()?),
+        _ => return None,
+    };
+    let points = ron::de::from_str::<Vec<(f64, f64)>>(points).ok()?;
+    Some((points, interpolation))
+}
+
+/// Replaces the interpolation suffix of the points input `s`, if any, by `interpolation`.
+pub fn with_interpolation(s: &str, interpolation: Interpolation) -> String {
+    let s = s.trim_end();
+    let s = match s.rsplit_once(',') {
+        Some((points, suffix)) if suffix.parse::<Interpolation>().is_ok() => points,
+        _ => s,
+    };
+    format!("{}, {}", s, interpolation)
+}
+
+/// Spline through `points` with the given interpolation.
+///
+/// Catmull-Rom splines get an extra key at each end, extrapolated linearly,
+/// so that they are defined up to the first and last points.
+pub fn spline(points: &[(f64, f64)], interpolation: Interpolation) -> Spline<f64, f64> {
+    use splines::interpolation::Interpolation as Mode;
+
+    let mut points = points.to_vec();
+    points.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(core::cmp::Ordering::Equal));
+    let n = points.len();
+    let mode = |i: usize| match interpolation {
+        Interpolation::Linear => Mode::Linear,
+        Interpolation::StepLeft => Mode::Step(1.),
+        Interpolation::StepRight => Mode::Step(0.),
+        Interpolation::CatmullRom => Mode::CatmullRom,
+        Interpolation::Cosine => Mode::Cosine,
+        Interpolation::Bezier => {
+            // Control values along the tangent given by finite differences, a third
+            // of the way into the interval on each side, whatever their widths.
+            let (x, y) = points[i];
+            let (before, after) = (points[i.saturating_sub(1)], points[(i + 1).min(n - 1)]);
+            let slope = if after.0 > before.0 {
+                (after.1 - before.1) / (after.0 - before.0)
+            } else {
+                0.
+            };
+            Mode::StrokeBezier(
+                y - slope * (x - before.0) / 3.,
+                y + slope * (after.0 - x) / 3.,
+            )
+        }
+    };
+    let mut keys: Vec<Key<f64, f64>> = (0..n)
+        .map(|i| Key::new(points[i].0, points[i].1, mode(i)))
+        .collect();
+    if interpolation == Interpolation::CatmullRom && n >= 2 {
+        let extrapolate = |(x0, y0): (f64, f64), (x1, y1): (f64, f64)| {
+            Key::new(2. * x0 - x1, 2. * y0 - y1, Mode::CatmullRom)
+        };
+        keys.insert(0, extrapolate(points[0], points[1]));
+        keys.push(extrapolate(points[n - 1], points[n - 2]));
+    }
+    Spline::from_iter(keys)
+}
+
+/// Keys of `spline` at the points it was built from, without the extra keys of Catmull-Rom.
+fn data_keys(spline: &Spline<f64, f64>, interpolation: Interpolation) -> &[Key<f64, f64>] {
+    let keys = spline.keys();
+    if interpolation == Interpolation::CatmullRom && keys.len() >= 4 {
+        &keys[1..keys.len() - 1]
+    } else {
+        keys
+    }
+}
+
+/// The points `spline` was built from, sorted by `x`.
+pub fn data(spline: &Spline<f64, f64>, interpolation: Interpolation) -> Vec<(f64, f64)> {
+    data_keys(spline, interpolation)
+        .iter()
+        .map(|key| (key.t, key.value))
+        .collect()
+}
+
+/// Value of `spline` at `x`, which is constant outside of the points.
+pub fn eval(spline: &Spline<f64, f64>, interpolation: Interpolation, x: f64) -> f64 {
+    if interpolation == Interpolation::CatmullRom {
+        if let [first, .., last] = data_keys(spline, interpolation) {
+            if x <= first.t {
+                return first.value;
+            }
+            if x >= last.t {
+                return last.value;
+            }
+        }
+    }
+    spline.clamped_sample(x).unwrap_or(f64::NAN)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interpolations() {
+        let points = [(0., 0.), (1., 1.), (2., 0.), (3., 1.)];
+        for &interpolation in &Interpolation::ALL {
+            let spline = spline(&points, interpolation);
+            // Steps to the right take the value of the next point at each point.
+            if interpolation != Interpolation::StepRight {
+                for &(x, y) in &points {
+                    let value = eval(&spline, interpolation, x);
+                    assert!((value - y).abs() < 1e-9, "{} at {}", interpolation, x);
+                }
+            }
+            assert_eq!(eval(&spline, interpolation, -1.), 0.);
+            assert_eq!(eval(&spline, interpolation, 4.), 1.);
+            assert!(eval(&spline, interpolation, 1.5).is_finite());
+        }
+        let linear = spline(&points, Interpolation::Linear);
+        assert_eq!(eval(&linear, Interpolation::Linear, 0.5), 0.5);
+        let left = spline(&points, Interpolation::StepLeft);
+        assert_eq!(eval(&left, Interpolation::StepLeft, 0.5), 0.);
+        let right = spline(&points, Interpolation::StepRight);
+        assert_eq!(eval(&right, Interpolation::StepRight, 0.5), 1.);
+        let catmull_rom = spline(&points, Interpolation::CatmullRom);
+        assert_eq!(data(&catmull_rom, Interpolation::CatmullRom), points);
+    }
+
+    #[test]
+    fn bezier_uneven() {
+        // Increasing data at uneven steps gives an increasing curve, without overshoot.
+        let points = [(0., 0.), (1., 1.), (10., 10.), (10.5, 12.)];
+        let bezier = spline(&points, Interpolation::Bezier);
+        let values: Vec<f64> = itertools_num::linspace(0., 10.5, 1000)
+            .map(|x| eval(&bezier, Interpolation::Bezier, x))
+            .collect();
+        assert!(values.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!((eval(&bezier, Interpolation::Bezier, 0.5) - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn syntax() {
+        let (points, interpolation) = parse_points("[(0, 1), (1, 2)], step-left").unwrap();
+        assert_eq!(points, vec![(0., 1.), (1., 2.)]);
+        assert_eq!(interpolation, Interpolation::StepLeft);
+        assert_eq!(parse_points("[(0, 1)]").unwrap().1, Interpolation::Cosine);
+        assert!(parse_points("[(0, 1)], quadratic").is_none());
+        assert_eq!(
+            with_interpolation("[(0, 1)], linear", Interpolation::Bezier),
+            "[(0, 1)], bezier"
+        );
+        assert_eq!(
+            with_interpolation("[(0, 1)]", Interpolation::Linear),
+            "[(0, 1)], linear"
+        );
+    }
+}
@@ -0,0 +1,141 @@
+use super::split_top_level;
+use crate::numerics::{freedman_diaconis, sturges, MAX_BINS};
+use serde::{Deserialize, Serialize};
+use yew::prelude::*;
+
+/// Parses a list of plain numbers, `[x_1, x_2, ...]`, sorting them.
+pub fn parse_sample(s: &str) -> Option<Vec<f64>> {
+    if split_top_level(s, ",").len() != 1 {
+        return None;
+    }
+    let mut values = ron::de::from_str::<Vec<f64>>(s.trim()).ok()?;
+    if values.is_empty() || !values.iter().all(|value| value.is_finite()) {
+        return None;
+    }
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    Some(values)
+}
+
+/// Rule choosing the number of bins of a histogram.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Binning {
+    /// The number of bins chosen by the user.
+    Count,
+    #[default]
+    Sturges,
+    FreedmanDiaconis,
+}
+
+impl Binning {
+    pub const ALL: [Binning; 3] = [Binning::Count, Binning::Sturges, Binning::FreedmanDiaconis];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Binning::Count => "fixed count",
+            Binning::Sturges => "Sturges",
+            Binning::FreedmanDiaconis => "Freedman-Diaconis",
+        }
+    }
+}
+
+/// How a sample of numbers is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Distribution {
+    /// Whether the histogram, normalized as a density, is drawn.
+    pub histogram: bool,
+    pub binning: Binning,
+    /// Number of bins of the histogram, if chosen by the user.
+    pub bins: usize,
+    /// Whether the empirical cumulative distribution function is drawn.
+    pub ecdf: bool,
+    /// Whether the Gaussian kernel density estimate is drawn.
+    pub kde: bool,
+    /// Factor multiplying the bandwidth given by Silverman's rule.
+    pub bandwidth_factor: f64,
+}
+
+impl Default for Distribution {
+    fn default() -> Self {
+        Distribution {
+            histogram: true,
+            binning: Binning::default(),
+            bins: 10,
+            ecdf: false,
+            kde: false,
+            bandwidth_factor: 1.,
+        }
+    }
+}
+
+impl Distribution {
+    /// Number of bins of the histogram of the `sorted` values.
+    pub fn bins(&self, sorted: &[f64]) -> usize {
+        match self.binning {
+            Binning::Count => self.bins.clamp(1, MAX_BINS),
+            Binning::Sturges => sturges(sorted.len()),
+            Binning::FreedmanDiaconis => freedman_diaconis(sorted),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SetDistribution {
+    Histogram,
+    Binning(ChangeData),
+    Bins(ChangeData),
+    Ecdf,
+    Kde,
+    BandwidthFactor(ChangeData),
+}
+
+impl Distribution {
+    pub fn update(&mut self, set: SetDistribution) -> ShouldRender {
+        match set {
+            SetDistribution::Histogram => {
+                self.histogram = !self.histogram;
+                true
+            }
+            SetDistribution::Binning(data) => {
+                if let ChangeData::Select(element) = data {
+                    log::trace!("Trying to change binning rule to {}", element.value());
+                    if let Some(binning) = Binning::ALL
+                        .iter()
+                        .find(|binning| binning.name() == element.value())
+                    {
+                        self.binning = *binning;
+                    }
+                }
+                true
+            }
+            SetDistribution::Bins(data) => {
+                if let ChangeData::Value(x) = data {
+                    log::trace!("Trying to change number of bins to {}", x);
+                    if let Ok(proposal) = x.parse::<usize>() {
+                        self.bins = proposal.clamp(1, MAX_BINS);
+                    }
+                }
+                true
+            }
+            SetDistribution::Ecdf => {
+                self.ecdf = !self.ecdf;
+                true
+            }
+            SetDistribution::Kde => {
+                self.kde = !self.kde;
+                true
+            }
+            SetDistribution::BandwidthFactor(data) => {
+                if let ChangeData::Value(x) = data {
+                    log::trace!("Trying to change bandwidth factor to {}", x);
+                    if let Ok(proposal) = x.parse::<f64>() {
+                        if proposal > 0. {
+                            self.bandwidth_factor = proposal;
+                        }
+                    }
+                }
+                true
+            }
+        }
+    }
+}
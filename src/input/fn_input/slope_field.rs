@@ -0,0 +1,34 @@
+use super::{split_top_level, Expression};
+
+/// Parses `y' = f(x, y)`, optionally followed by `, [(x0, y0), ...]`,
+/// the initial conditions of the solutions to draw.
+pub fn parse_slope_field(s: &str) -> Option<(Expression, Vec<(f64, f64)>)> {
+    let (lhs, rhs) = s.split_once('=')?;
+    if lhs.trim() != "y'" || rhs.starts_with('=') {
+        return None;
+    }
+    let parts = split_top_level(rhs, ",");
+    let (expression, initial_conditions) = match parts.as_slice() {
+        [expression] => (expression, vec![]),
+        [expression, initial_conditions] => (
+            expression,
+            ron::de::from_str::<Vec<(f64, f64)>>(initial_conditions).ok()?,
+        ),
+        _ => return None,
+    };
+    Some((expression.parse().ok()?, initial_conditions))
+}
+
+/// Adds `(x, y)` to the initial conditions of the slope field input `s`.
+pub fn with_initial_condition(s: &str, (x, y): (f64, f64)) -> Option<String> {
+    let (_, mut initial_conditions) = parse_slope_field(s)?;
+    initial_conditions.push((x, y));
+    let (_, rhs) = s.split_once('=')?;
+    let expression = split_top_level(rhs, ",")[0].trim();
+    let initial_conditions = initial_conditions
+        .iter()
+        .map(|(x, y)| format!("({}, {})", x, y))
+        .collect::<Vec<String>>()
+        .join(", ");
+    Some(format!("y' = {}, [{}]", expression, initial_conditions))
+}
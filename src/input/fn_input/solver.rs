@@ -0,0 +1,70 @@
+use crate::numerics::Method;
+use serde::{Deserialize, Serialize};
+use yew::prelude::*;
+
+/// Numerical settings used to solve an initial value problem.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Solver {
+    pub method: Method,
+    /// Error allowed at each step of adaptive methods.
+    pub tolerance: f64,
+}
+
+impl Default for Solver {
+    fn default() -> Self {
+        Solver {
+            method: Method::default(),
+            tolerance: 1e-6,
+        }
+    }
+}
+
+/// Everything a solution depends on besides its own input, so that it is only
+/// computed again when one of these changes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SolutionKey {
+    pub domain: (f64, f64),
+    pub step: f64,
+    pub solver: Solver,
+    /// Values of the parameters the equation uses, directly or through calls.
+    pub parameters: Vec<(String, f64)>,
+    /// Inputs of the named functions the equation calls, directly or not.
+    pub definitions: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum SetSolver {
+    Method(ChangeData),
+    Tolerance(ChangeData),
+}
+
+impl Solver {
+    pub fn update(&mut self, set: SetSolver) -> ShouldRender {
+        match set {
+            SetSolver::Method(data) => {
+                if let ChangeData::Select(element) = data {
+                    log::trace!("Trying to change method to {}", element.value());
+                    if let Some(method) = Method::ALL
+                        .iter()
+                        .find(|method| method.name() == element.value())
+                    {
+                        self.method = *method;
+                    }
+                }
+                true
+            }
+            SetSolver::Tolerance(data) => {
+                if let ChangeData::Value(x) = data {
+                    log::trace!("Trying to change tolerance to {}", x);
+                    if let Ok(proposal) = x.parse::<f64>() {
+                        if proposal > 0. {
+                            self.tolerance = proposal;
+                        }
+                    }
+                }
+                true
+            }
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use super::{Context, FnInput};
+use serde::{Deserialize, Serialize};
+use yew::prelude::*;
+
+/// Largest order of the Taylor polynomials, whose derivatives are computed symbolically.
+///
+/// The derivatives grow about fourfold with each order, so that those of order 9 of
+/// `exp(-x^2)` already take seconds, while the order is changed with a slider.
+pub const MAX_TAYLOR_ORDER: usize = 6;
+
+/// Taylor polynomial drawn along with an analytical function.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Taylor {
+    pub enabled: bool,
+    pub center: f64,
+    pub order: usize,
+    /// Error below which the approximation is shaded.
+    pub tolerance: f64,
+}
+
+impl Default for Taylor {
+    fn default() -> Self {
+        Taylor {
+            enabled: false,
+            center: 0.,
+            order: 3,
+            tolerance: 0.01,
+        }
+    }
+}
+
+impl Taylor {
+    /// Coefficients of the polynomial in powers of `x - center`, unless `fn_input` is
+    /// not analytical or its derivatives can not be computed symbolically.
+    ///
+    /// Orders above [`MAX_TAYLOR_ORDER`], stored by earlier versions, are capped.
+    pub fn coefficients(&self, fn_input: &FnInput, context: &Context) -> Option<Vec<f64>> {
+        let derivatives = fn_input.derivative_expressions(self.order.min(MAX_TAYLOR_ORDER))?;
+        let bindings = [("x", self.center)];
+        let mut coefficients = vec![fn_input.kind().eval(context, self.center)];
+        let mut factorial = 1.;
+        for (k, derivative) in derivatives.iter().enumerate() {
+            factorial *= (k + 1) as f64;
+            coefficients.push(derivative.eval(context, &bindings) / factorial);
+        }
+        Some(coefficients)
+    }
+}
+
+/// Value at `x` of the polynomial with `coefficients` in powers of `x - center`.
+pub fn polynomial(coefficients: &[f64], center: f64, x: f64) -> f64 {
+    coefficients
+        .iter()
+        .rev()
+        .fold(0., |value, coefficient| value * (x - center) + coefficient)
+}
+
+/// Polynomial with `coefficients` in powers of `x - center`, as in `1 + 0.5 (x - 2)^2`,
+/// leaving out zero terms.
+pub fn polynomial_string(coefficients: &[f64], center: f64) -> String {
+    let base = if center == 0. {
+        "x".to_string()
+    } else if center < 0. {
+        format!("(x + {})", -center)
+    } else {
+        format!("(x - {})", center)
+    };
+    let mut terms = coefficients
+        .iter()
+        .enumerate()
+        .filter(|(_, coefficient)| **coefficient != 0.)
+        .map(|(k, coefficient)| {
+            let power = match k {
+                0 => String::new(),
+                1 => format!(" {}", base),
+                _ => format!(" {}^{}", base, k),
+            };
+            (coefficient, power)
+        });
+    let mut string = match terms.next() {
+        Some((coefficient, power)) => format!("{:.4}{}", coefficient, power),
+        None => return "0".to_string(),
+    };
+    for (coefficient, power) in terms {
+        let sign = if *coefficient < 0. { '-' } else { '+' };
+        string.push_str(&format!(" {} {:.4}{}", sign, coefficient.abs(), power));
+    }
+    string
+}
+
+#[derive(Debug)]
+pub enum SetTaylor {
+    Enabled,
+    Center(ChangeData),
+    Order(String),
+    Tolerance(ChangeData),
+}
+
+impl Taylor {
+    pub fn update(&mut self, set: SetTaylor) -> ShouldRender {
+        match set {
+            SetTaylor::Enabled => {
+                self.enabled = !self.enabled;
+                true
+            }
+            SetTaylor::Center(data) => {
+                if let ChangeData::Value(x) = data {
+                    log::trace!("Trying to change center of the Taylor polynomial to {}", x);
+                    if let Ok(proposal) = x.parse::<f64>() {
+                        self.center = proposal;
+                    }
+                }
+                true
+            }
+            SetTaylor::Order(x) => {
+                log::trace!("Trying to change order of the Taylor polynomial to {}", x);
+                if let Ok(proposal) = x.parse::<usize>() {
+                    self.order = proposal.min(MAX_TAYLOR_ORDER);
+                }
+                true
+            }
+            SetTaylor::Tolerance(data) => {
+                if let ChangeData::Value(x) = data {
+                    log::trace!(
+                        "Trying to change tolerance of the Taylor polynomial to {}",
+                        x
+                    );
+                    if let Ok(proposal) = x.parse::<f64>() {
+                        self.tolerance = proposal.abs();
+                    }
+                }
+                true
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::FnInputKind;
+    use core::str::FromStr;
+
+    #[test]
+    fn polynomials() {
+        let coefficients = [1., 0., -0.5];
+        assert_eq!(polynomial(&coefficients, 2., 4.), -1.);
+        assert_eq!(
+            polynomial_string(&coefficients, 2.),
+            "1.0000 - 0.5000 (x - 2)^2"
+        );
+        assert_eq!(polynomial_string(&[0., 3.], -1.), "3.0000 (x + 1)");
+        assert_eq!(polynomial_string(&[], 0.), "0");
+
+        let mut exponential = FnInput::default();
+        exponential.set_kind(FnInputKind::from_str("exp(x)").unwrap());
+        let taylor = Taylor {
+            order: 4,
+            ..Taylor::default()
+        };
+        let coefficients = taylor
+            .coefficients(&exponential, &Context::default())
+            .unwrap();
+        for (coefficient, expected) in coefficients.iter().zip(&[1., 1., 0.5, 1. / 6., 1. / 24.]) {
+            assert!((coefficient - expected).abs() < 1e-12);
+        }
+    }
+}
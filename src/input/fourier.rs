@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+use yew::prelude::*;
+
+/// Panel showing the spectrum of a function of `x` in a chart under the plot.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Fourier {
+    pub enabled: bool,
+    /// Index of the function input that is transformed.
+    pub function: usize,
+}
+
+#[derive(Debug)]
+pub enum SetFourier {
+    Enabled,
+    Function(ChangeData),
+}
+
+impl Fourier {
+    pub fn update(&mut self, set: SetFourier) -> ShouldRender {
+        match set {
+            SetFourier::Enabled => {
+                self.enabled = !self.enabled;
+                true
+            }
+            SetFourier::Function(data) => {
+                if let ChangeData::Select(element) = data {
+                    log::trace!(
+                        "Trying to change transformed function to {}",
+                        element.value()
+                    );
+                    if let Ok(index) = element.value().parse::<usize>() {
+                        self.function = index;
+                    }
+                }
+                true
+            }
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+use yew::prelude::*;
+
+/// Colors used for the values of the function in heatmap mode, from the lowest to the highest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Colormap {
+    #[default]
+    Viridis,
+    Grayscale,
+    Bone,
+    Copper,
+    Volcano,
+}
+
+impl Colormap {
+    pub const ALL: [Colormap; 5] = [
+        Colormap::Viridis,
+        Colormap::Grayscale,
+        Colormap::Bone,
+        Colormap::Copper,
+        Colormap::Volcano,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Colormap::Viridis => "viridis",
+            Colormap::Grayscale => "grayscale",
+            Colormap::Bone => "bone",
+            Colormap::Copper => "copper",
+            Colormap::Volcano => "volcano",
+        }
+    }
+}
+
+/// Settings of the heatmap mode.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Heatmap {
+    pub colormap: Colormap,
+    /// Values at which iso-lines are drawn, sorted.
+    pub levels: Vec<f64>,
+}
+
+impl Heatmap {
+    /// Levels as written in their input, separated by commas.
+    pub fn levels_string(&self) -> String {
+        self.levels
+            .iter()
+            .map(f64::to_string)
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+}
+
+/// Parses levels separated by commas or whitespace, such as `0.1, 0.5, 1`.
+pub fn parse_levels(s: &str) -> Option<Vec<f64>> {
+    let mut levels = s
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|level| !level.is_empty())
+        .map(|level| level.parse::<f64>().ok().filter(|level| level.is_finite()))
+        .collect::<Option<Vec<f64>>>()?;
+    levels.sort_by(|a, b| a.partial_cmp(b).unwrap_or(core::cmp::Ordering::Equal));
+    levels.dedup();
+    Some(levels)
+}
+
+#[derive(Debug)]
+pub enum SetHeatmap {
+    Colormap(ChangeData),
+    Levels(ChangeData),
+}
+
+impl Heatmap {
+    pub fn update(&mut self, set: SetHeatmap) -> ShouldRender {
+        match set {
+            SetHeatmap::Colormap(data) => {
+                if let ChangeData::Select(element) = data {
+                    log::trace!("Trying to change colormap to {}", element.value());
+                    if let Some(colormap) = Colormap::ALL
+                        .iter()
+                        .find(|colormap| colormap.name() == element.value())
+                    {
+                        self.colormap = *colormap;
+                    }
+                }
+                true
+            }
+            SetHeatmap::Levels(data) => {
+                if let ChangeData::Value(s) = data {
+                    log::trace!("Trying to change levels to {}", s);
+                    match parse_levels(&s) {
+                        Some(levels) => self.levels = levels,
+                        None => log::error!("Levels must be numbers separated by commas: {}", s),
+                    }
+                }
+                true
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels() {
+        assert_eq!(parse_levels("1, -0.5 2,1"), Some(vec![-0.5, 1., 2.]));
+        assert_eq!(parse_levels(""), Some(vec![]));
+        assert_eq!(parse_levels("1, a"), None);
+        let heatmap = Heatmap {
+            levels: vec![-0.5, 2.],
+            ..Heatmap::default()
+        };
+        assert_eq!(heatmap.levels_string(), "-0.5, 2");
+    }
+}
@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+use yew::prelude::*;
+
+/// Free variable of the functions, whose value is chosen with a slider.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Parameter {
+    pub name: String,
+    pub min: f64,
+    pub max: f64,
+    pub step: f64,
+    pub value: f64,
+}
+
+impl Parameter {
+    pub fn new(name: String) -> Self {
+        Parameter {
+            name,
+            min: -5.,
+            max: 5.,
+            step: 0.1,
+            value: 1.,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SetParameter {
+    Value(String),
+    Min(ChangeData),
+    Max(ChangeData),
+    Step(ChangeData),
+}
+
+impl Parameter {
+    pub fn update(&mut self, set: SetParameter) -> ShouldRender {
+        match set {
+            SetParameter::Value(x) => {
+                log::trace!("Trying to change parameter {} to {}", self.name, x);
+                if let Ok(proposal) = x.parse::<f64>() {
+                    self.value = proposal.max(self.min).min(self.max);
+                }
+                true
+            }
+            SetParameter::Min(data) => {
+                if let ChangeData::Value(x) = data {
+                    log::trace!("Trying to change min of parameter {} to {}", self.name, x);
+                    if let Ok(proposal) = x.parse::<f64>() {
+                        self.min = proposal.min(self.max);
+                        self.value = self.value.max(self.min);
+                    }
+                }
+                true
+            }
+            SetParameter::Max(data) => {
+                if let ChangeData::Value(x) = data {
+                    log::trace!("Trying to change max of parameter {} to {}", self.name, x);
+                    if let Ok(proposal) = x.parse::<f64>() {
+                        self.max = proposal.max(self.min);
+                        self.value = self.value.min(self.max);
+                    }
+                }
+                true
+            }
+            SetParameter::Step(data) => {
+                if let ChangeData::Value(x) = data {
+                    log::trace!("Trying to change step of parameter {} to {}", self.name, x);
+                    if let Ok(proposal) = x.parse::<f64>() {
+                        if proposal > 0. {
+                            self.step = proposal;
+                        }
+                    }
+                }
+                true
+            }
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use super::fn_input::{split_top_level, Expression};
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_SYSTEM: &str = "x' = y, y' = -sin(x) - y/2";
+
+/// Autonomous system `x' = f(x, y), y' = g(x, y)` drawn in phase portrait mode.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PhasePortrait {
+    pub text: String,
+    pub f: Expression,
+    pub g: Expression,
+    /// Points the drawn trajectories go through.
+    pub initial_points: Vec<(f64, f64)>,
+}
+
+impl Default for PhasePortrait {
+    fn default() -> Self {
+        let (f, g) = parse_system(DEFAULT_SYSTEM).unwrap();
+        PhasePortrait {
+            text: DEFAULT_SYSTEM.to_string(),
+            f,
+            g,
+            initial_points: vec![],
+        }
+    }
+}
+
+impl PhasePortrait {
+    /// Replaces the system by the one written in `s`, keeping the initial points,
+    /// unless `s` is not a valid system.
+    pub fn set_system(&mut self, s: String) -> Option<&mut Self> {
+        let (f, g) = parse_system(&s)?;
+        self.text = s;
+        self.f = f;
+        self.g = g;
+        Some(self)
+    }
+
+    /// Variables of the system other than `x` and `y`, which are treated as parameters.
+    pub fn free_variables(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .f
+            .variables()
+            .into_iter()
+            .chain(self.g.variables())
+            .filter(|name| name != "x" && name != "y")
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+}
+
+/// Parses `x' = f(x, y), y' = g(x, y)`, where the equations may come in any order.
+pub fn parse_system(s: &str) -> Option<(Expression, Expression)> {
+    let (mut f, mut g) = (None, None);
+    for equation in split_top_level(s, ",") {
+        let (lhs, rhs) = equation.split_once('=')?;
+        let expression: Expression = rhs.parse().ok()?;
+        let slot = match lhs.trim() {
+            "x'" => &mut f,
+            "y'" => &mut g,
+            _ => return None,
+        };
+        if slot.replace(expression).is_some() {
+            return None;
+        }
+    }
+    Some((f?, g?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn system() {
+        let mut phase_portrait = PhasePortrait::default();
+        assert!(phase_portrait
+            .set_system("y' = a*x, x' = -y + b".to_string())
+            .is_some());
+        assert_eq!(phase_portrait.free_variables(), vec!["a", "b"]);
+        for s in &[
+            "x' = y",
+            "x' = y, x' = x",
+            "x' = y, z' = x",
+            "x' = y, y' = (x",
+        ] {
+            assert!(parse_system(s).is_none(), "{}", s);
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use serde::{Deserialize, Serialize};
+use yew::prelude::*;
+
+/// Tool drawing the tangent line of a function of `x` at a point, and optionally
+/// its normal line and osculating circle.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Tangent {
+    pub enabled: bool,
+    /// Index of the function input the tool applies to.
+    pub function: usize,
+    /// Point of tangency, which clicking the plot also sets.
+    pub x0: f64,
+    pub normal: bool,
+    pub osculating_circle: bool,
+}
+
+impl Default for Tangent {
+    fn default() -> Self {
+        Tangent {
+            enabled: false,
+            function: 0,
+            x0: 0.,
+            normal: true,
+            osculating_circle: false,
+        }
+    }
+}
+
+/// Value, slope and second derivative of a function at a point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Linearization {
+    pub point: (f64, f64),
+    pub slope: f64,
+    pub second_derivative: f64,
+}
+
+impl Linearization {
+    /// Value of the tangent line at `x`.
+    pub fn tangent(&self, x: f64) -> f64 {
+        self.point.1 + self.slope * (x - self.point.0)
+    }
+
+    /// Equation of the tangent line, as `y = m x + b`.
+    pub fn equation(&self) -> String {
+        let intercept = self.tangent(0.);
+        let sign = if intercept < 0. { '-' } else { '+' };
+        format!("y = {:.4} x {} {:.4}", self.slope, sign, intercept.abs())
+    }
+
+    /// Center and radius of the osculating circle, unless the curvature is zero.
+    pub fn osculating_circle(&self) -> Option<((f64, f64), f64)> {
+        if self.second_derivative == 0. || !self.second_derivative.is_finite() {
+            return None;
+        }
+        let stretch = 1. + self.slope * self.slope;
+        let center = (
+            self.point.0 - self.slope * stretch / self.second_derivative,
+            self.point.1 + stretch / self.second_derivative,
+        );
+        Some((center, stretch.powf(1.5) / self.second_derivative.abs()))
+    }
+}
+
+#[derive(Debug)]
+pub enum SetTangent {
+    Enabled,
+    Function(ChangeData),
+    X0(ChangeData),
+    Normal,
+    OsculatingCircle,
+}
+
+impl Tangent {
+    pub fn update(&mut self, set: SetTangent) -> ShouldRender {
+        match set {
+            SetTangent::Enabled => {
+                self.enabled = !self.enabled;
+                true
+            }
+            SetTangent::Function(data) => {
+                if let ChangeData::Select(element) = data {
+                    log::trace!("Trying to change tangent function to {}", element.value());
+                    if let Ok(index) = element.value().parse::<usize>() {
+                        self.function = index;
+                    }
+                }
+                true
+            }
+            SetTangent::X0(data) => {
+                if let ChangeData::Value(x) = data {
+                    log::trace!("Trying to change point of tangency to {}", x);
+                    if let Ok(proposal) = x.parse::<f64>() {
+                        self.x0 = proposal;
+                    }
+                }
+                true
+            }
+            SetTangent::Normal => {
+                self.normal = !self.normal;
+                true
+            }
+            SetTangent::OsculatingCircle => {
+                self.osculating_circle = !self.osculating_circle;
+                true
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parabola() {
+        // y = x^2 at x = 1
+        let linearization = Linearization {
+            point: (1., 1.),
+            slope: 2.,
+            second_derivative: 2.,
+        };
+        assert_eq!(linearization.equation(), "y = 2.0000 x - 1.0000");
+        let (center, radius) = linearization.osculating_circle().unwrap();
+        assert!((center.0 + 4.).abs() < 1e-12 && (center.1 - 3.5).abs() < 1e-12);
+        assert!((radius - 5f64.powf(1.5) / 2.).abs() < 1e-12);
+        let line = Linearization {
+            second_derivative: 0.,
+            ..linearization
+        };
+        assert_eq!(line.osculating_circle(), None);
+    }
+}
@@ -1,25 +1,177 @@
-use core::str::FromStr;
 use itertools::Itertools;
 use plotters::prelude::*;
 use plotters_canvas::CanvasBackend;
-use web_sys::HtmlCanvasElement;
+use wasm_bindgen::{Clamped, JsCast};
+use web_sys::{CanvasRenderingContext2d, File, HtmlCanvasElement, ImageData};
 use yew::prelude::*;
+use yew::services::reader::{FileData, ReaderService, ReaderTask};
 
 mod input;
-use input::{FnInput, FnInputKind, Input, Set};
+mod numerics;
+use core::cell::{Ref, RefCell};
+use core::ops::Range;
+use input::{
+    initial_value_problem, parse_definition, piecewise, points, slope_field, taylor, Binning,
+    Colormap, Context, DataFile, Dependencies, DisplayMode, FitModel, FitResult, FnInput,
+    FnInputKind, Input, Interpolation, Linearization, MarkerShape, Mode, Set, SetDataFile,
+    SetDistribution, SetFit, SetFourier, SetHeatmap, SetIntegral, SetParameter, SetPointStyle,
+    SetSolver, SetTangent, SetTaylor, MAX_DERIVATIVES, MAX_FIT_DEGREE, MAX_FOURIER_ORDER,
+    MAX_TAYLOR_ORDER,
+};
+use num_complex::Complex;
+use numerics::{
+    boundary, cells, color, cumulative_integral, derivative, ecdf, extrema, fixed_points,
+    fourier_coefficients, fourier_series, histogram, integrate, join, kde, silverman_bandwidth,
+    solve, solve_system, spectrum, zeros, Extremum, Grid, Method, Quadrature, Segment, MAX_BINS,
+};
+use plotters::coord::{types::RangedCoordf64, Shift};
+
+/// Maximum number of samples along each axis of a surface, which is drawn as polygons.
+const MAX_SURFACE_RESOLUTION: usize = 60;
+
+/// Time over which trajectories of the phase portrait are followed, in both directions.
+const TRAJECTORY_TIME: f64 = 20.;
+
+/// Error allowed in the area under a curve, relative to its value.
+const AREA_TOLERANCE: f64 = 1e-10;
+
+/// Fraction of the height of the canvas taken by the spectrum, when it is shown.
+const SPECTRUM_HEIGHT: f64 = 0.4;
+
+/// Width of the colorbar of the heatmap, labels included, in pixels.
+const COLORBAR_WIDTH: u32 = 80;
+
+/// Pixels covered by the plotting area of a chart in the plane, and the
+/// coordinates they represent, so that clicks can be located.
+#[derive(Debug, Clone, PartialEq)]
+struct PlotArea {
+    pixels: (Range<i32>, Range<i32>),
+    domain: (f64, f64),
+    range: (f64, f64),
+}
+
+impl PlotArea {
+    fn new(chart: &Chart, domain: (f64, f64), range: (f64, f64)) -> Self {
+        PlotArea {
+            pixels: chart.plotting_area().get_pixel_range(),
+            domain,
+            range,
+        }
+    }
+
+    /// Coordinates of the pixel `(i, j)` of the canvas, if it is inside the plotting area.
+    fn coordinates(&self, (i, j): (i32, i32)) -> Option<(f64, f64)> {
+        let (columns, rows) = &self.pixels;
+        if !columns.contains(&i) || !rows.contains(&j) {
+            return None;
+        }
+        let s = f64::from(i - columns.start) / f64::from(columns.end - columns.start);
+        let t = f64::from(j - rows.start) / f64::from(rows.end - rows.start);
+        Some((
+            self.domain.0 + s * (self.domain.1 - self.domain.0),
+            self.range.1 - t * (self.range.1 - self.range.0),
+        ))
+    }
+}
+
+/// Notable point of the visible functions of `x`, marked on the chart and listed under them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KeyPointKind {
+    Zero,
+    Extremum(Extremum),
+    Intersection,
+}
+
+impl KeyPointKind {
+    fn name(&self) -> &'static str {
+        match self {
+            KeyPointKind::Zero => "zero",
+            KeyPointKind::Extremum(Extremum::Minimum) => "minimum",
+            KeyPointKind::Extremum(Extremum::Maximum) => "maximum",
+            KeyPointKind::Intersection => "intersection",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct KeyPoint {
+    kind: KeyPointKind,
+    /// Functions the point belongs to, by name or as written.
+    functions: String,
+    point: (f64, f64),
+}
+
+/// Results shown by both the view and the plot, computed once per render.
+struct Analysis {
+    dependencies: Dependencies,
+    /// Key points of the functions of `x`, if they are marked.
+    key_points: Vec<KeyPoint>,
+    /// Linearization for the tangent tool, if it is enabled.
+    linearization: Option<Linearization>,
+    /// Least-squares fit of each function input.
+    fits: Vec<Option<FitResult>>,
+}
+
+type Chart<'a> = ChartContext<'a, CanvasBackend, Cartesian2d<RangedCoordf64, RangedCoordf64>>;
 
 enum Msg {
     Left(ChangeData),
     Right(ChangeData),
+    Front(ChangeData),
+    Back(ChangeData),
+    Bottom(ChangeData),
+    Top(ChangeData),
+    AutoRange,
     AddFnInput,
     Function(usize, ChangeData),
+    Interpolation(usize, ChangeData),
+    PointStyle(usize, SetPointStyle),
+    Solver(usize, SetSolver),
+    Derivatives(usize, ChangeData),
+    Integral(usize, SetIntegral),
+    Taylor(usize, SetTaylor),
+    FourierSeries(usize, ChangeData),
+    Fit(usize, SetFit),
+    Distribution(usize, SetDistribution),
+    AddFit(usize),
     ToggleFunction(usize),
+    Parameter(usize, SetParameter),
+    LoadFile(File),
+    FileLoaded(FileData),
+    DataFile(SetDataFile),
+    AddDataFile,
+    AddDataFileSamples,
+    DiscardDataFile,
+    Mode(ChangeData),
+    ComplexFunction(ChangeData),
+    ModulusContours,
+    PhaseSystem(ChangeData),
+    ClearTrajectories,
+    Heatmap(SetHeatmap),
+    Tangent(SetTangent),
+    Fourier(SetFourier),
+    DragStart(i32, i32),
+    Drag(i32, i32),
+    DragEnd,
+    Click(i32, i32),
     Auxiliary(Set),
 }
 struct Model {
     link: ComponentLink<Self>,
     canvas_ref: NodeRef,
     input: Input,
+    /// Data file whose columns are being chosen, not stored.
+    data_file: Option<DataFile>,
+    data_file_error: Option<String>,
+    /// Why the last complex function entered could not be parsed.
+    complex_function_error: Option<String>,
+    reader_task: Option<ReaderTask>,
+    /// Last position of the mouse while rotating the 3D view.
+    drag: Option<(i32, i32)>,
+    /// Plotting area of the last plot in the plane.
+    plot_area: Option<PlotArea>,
+    /// Analysis of the input, computed when first needed after an update.
+    analysis: RefCell<Option<Analysis>>,
 }
 impl Component for Model {
     type Message = Msg;
@@ -29,6 +181,13 @@ impl Component for Model {
             link,
             canvas_ref: NodeRef::default(),
             input: Input::restore_or_default(),
+            data_file: None,
+            data_file_error: None,
+            complex_function_error: None,
+            reader_task: None,
+            drag: None,
+            plot_area: None,
+            analysis: RefCell::default(),
         }
     }
     fn rendered(&mut self, _first_render: bool) {
@@ -36,11 +195,11 @@ impl Component for Model {
         canvas.set_width(self.input.canvas_size.0);
         canvas.set_height(self.input.canvas_size.1);
         let backend: CanvasBackend = CanvasBackend::with_canvas_object(canvas).unwrap();
-        self.plot(backend);
+        self.plot_area = self.plot(backend);
     }
 
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
-        match msg {
+        let should_render = match msg {
             Msg::Left(data) => {
                 if let ChangeData::Value(x) = data {
                     log::trace!("Trying to change left to {}", x);
@@ -59,6 +218,82 @@ impl Component for Model {
                 }
                 true
             }
+            Msg::Front(data) => {
+                if let ChangeData::Value(y) = data {
+                    log::trace!("Trying to change front to {}", y);
+                    let proposal: f64 = y.parse().unwrap();
+                    self.input.y_domain.0 = proposal.min(self.input.y_domain.1);
+                    self.input.store();
+                }
+                true
+            }
+            Msg::Back(data) => {
+                if let ChangeData::Value(y) = data {
+                    log::trace!("Trying to change back to {}", y);
+                    let proposal: f64 = y.parse().unwrap();
+                    self.input.y_domain.1 = proposal.max(self.input.y_domain.0);
+                    self.input.store();
+                }
+                true
+            }
+            Msg::Bottom(data) => {
+                if let ChangeData::Value(y) = data {
+                    log::trace!("Trying to change bottom to {}", y);
+                    let proposal: f64 = y.parse().unwrap();
+                    self.input.range.0 = proposal.min(self.input.range.1);
+                    self.input.store();
+                }
+                true
+            }
+            Msg::Top(data) => {
+                if let ChangeData::Value(y) = data {
+                    log::trace!("Trying to change top to {}", y);
+                    let proposal: f64 = y.parse().unwrap();
+                    self.input.range.1 = proposal.max(self.input.range.0);
+                    self.input.store();
+                }
+                true
+            }
+            Msg::AutoRange => {
+                self.input.auto_range = !self.input.auto_range;
+                self.input.store();
+                true
+            }
+            Msg::Derivatives(index, data) => {
+                if let ChangeData::Value(x) = data {
+                    log::trace!("Trying to draw {} derivatives of function {}", x, index);
+                    if let Ok(proposal) = x.parse::<usize>() {
+                        self.input.functions[index].derivatives = proposal.min(MAX_DERIVATIVES);
+                        self.input.store();
+                    }
+                }
+                true
+            }
+            Msg::Integral(index, set) => {
+                let should_render = self.input.functions[index].integral.update(set);
+                self.input.store();
+                should_render
+            }
+            Msg::Taylor(index, set) => {
+                let should_render = self.input.functions[index].taylor.update(set);
+                self.input.store();
+                should_render
+            }
+            Msg::FourierSeries(index, data) => {
+                if let ChangeData::Value(x) = data {
+                    log::trace!(
+                        "Trying to draw Fourier series of order {} of function {}",
+                        x,
+                        index
+                    );
+                    if let Ok(proposal) = x.parse::<usize>() {
+                        self.input.functions[index].fourier_series =
+                            proposal.min(MAX_FOURIER_ORDER);
+                        self.input.store();
+                    }
+                }
+                true
+            }
             Msg::ToggleFunction(index) => {
                 log::trace!("Trying to toggle function {}", index);
                 self.input.functions[index].toggle();
@@ -67,34 +302,254 @@ impl Component for Model {
                 true
             }
             Msg::Function(index, data) => {
-                if let ChangeData::Value(mut f) = data {
-                    log::trace!("Trying to change function index {} to {}", index, f);
-                    let kind = match FnInputKind::from_str(&f) {
-                        Ok(k) => {
-                            log::trace!("Identified function input of kind {:?}", k);
-                            k
-                        }
-                        Err(e) => {
-                            log::error!("{}\nInput: {}", e, f);
-                            log::warn!("Function changed to default input.");
-                            let fn_input = FnInput::default();
-                            f = fn_input.string.clone();
-                            fn_input.kind().clone()
-                        }
-                    };
-                    self.input.functions[index].set_kind(kind).set_string(f);
+                if let ChangeData::Value(f) = data {
+                    self.set_function(index, f);
+                }
+                true
+            }
+            Msg::Interpolation(index, data) => {
+                if let ChangeData::Select(element) = data {
+                    if let Ok(interpolation) = element.value().parse() {
+                        let f = points::with_interpolation(
+                            &self.input.functions[index].string,
+                            interpolation,
+                        );
+                        self.set_function(index, f);
+                    }
+                }
+                true
+            }
+            Msg::PointStyle(index, set) => {
+                let should_render = self.input.functions[index].point_style.update(set);
+                self.input.store();
+                should_render
+            }
+            Msg::Solver(index, set) => {
+                let should_render = self.input.functions[index].solver.update(set);
+                self.input.store();
+                should_render
+            }
+            Msg::LoadFile(file) => {
+                log::trace!("Trying to read file {}", file.name());
+                let callback = self.link.callback(Msg::FileLoaded);
+                match ReaderService::read_file(file, callback) {
+                    Ok(task) => self.reader_task = Some(task),
+                    Err(e) => log::error!("Failed to read file: {}", e),
+                }
+                false
+            }
+            Msg::FileLoaded(file) => {
+                self.reader_task = None;
+                match DataFile::parse(file.name, &file.content) {
+                    Ok(data_file) => {
+                        self.data_file = Some(data_file);
+                        self.data_file_error = None;
+                    }
+                    Err(e) => {
+                        log::error!("{}", e);
+                        self.data_file = None;
+                        self.data_file_error = Some(e.to_string());
+                    }
+                }
+                true
+            }
+            Msg::DataFile(set) => match &mut self.data_file {
+                Some(data_file) => data_file.update(set),
+                None => false,
+            },
+            Msg::AddDataFile => {
+                if let Some(data_file) = self.data_file.take() {
+                    self.input.functions.extend(data_file.fn_inputs());
                     self.input.store();
                 }
                 true
             }
+            Msg::AddDataFileSamples => {
+                if let Some(data_file) = self.data_file.take() {
+                    self.input.functions.extend(data_file.sample_inputs());
+                    self.input.store();
+                }
+                true
+            }
+            Msg::DiscardDataFile => {
+                self.data_file = None;
+                self.data_file_error = None;
+                true
+            }
+            Msg::Fit(index, set) => {
+                let should_render = self.input.functions[index].fit.update(set);
+                self.input.store();
+                should_render
+            }
+            Msg::Distribution(index, set) => {
+                let should_render = self.input.functions[index].distribution.update(set);
+                self.input.store();
+                should_render
+            }
+            Msg::AddFit(index) => {
+                let result = self.analysis().fits[index].clone();
+                match result {
+                    Some(result) => {
+                        self.input.functions.push(FnInput::default());
+                        self.set_function(self.input.functions.len() - 1, result.text);
+                        true
+                    }
+                    None => false,
+                }
+            }
             Msg::AddFnInput => {
                 self.input.functions.push(FnInput::default());
                 self.input.store();
                 true
             }
+            Msg::Parameter(index, set) => {
+                let should_render = self.input.parameters[index].update(set);
+                self.input.store();
+                should_render
+            }
+            Msg::Mode(data) => {
+                if let ChangeData::Select(element) = data {
+                    log::trace!("Trying to change mode to {}", element.value());
+                    if let Some(mode) = Mode::ALL.iter().find(|mode| mode.name() == element.value())
+                    {
+                        self.input.mode = *mode;
+                        self.input.sync_parameters();
+                        self.input.store();
+                    }
+                }
+                true
+            }
+            Msg::ComplexFunction(data) => {
+                if let ChangeData::Value(f) = data {
+                    log::trace!("Trying to change complex function to {}", f);
+                    match f.parse() {
+                        Ok(expression) => {
+                            self.input.complex_function = expression;
+                            self.complex_function_error = None;
+                            self.input.sync_parameters();
+                            self.input.store();
+                        }
+                        Err(e) => {
+                            log::error!("{}", e);
+                            self.complex_function_error = Some(e.to_string());
+                        }
+                    }
+                }
+                true
+            }
+            Msg::PhaseSystem(data) => {
+                if let ChangeData::Value(s) = data {
+                    log::trace!("Trying to change the autonomous system to {}", s);
+                    if self.input.phase_portrait.set_system(s.clone()).is_some() {
+                        self.input.sync_parameters();
+                        self.input.store();
+                    } else {
+                        log::error!(
+                            "The input {:?} is not of the form x' = f(x, y), y' = g(x, y).",
+                            s
+                        );
+                    }
+                }
+                true
+            }
+            Msg::ClearTrajectories => {
+                self.input.phase_portrait.initial_points.clear();
+                self.input.store();
+                true
+            }
+            Msg::ModulusContours => {
+                self.input.modulus_contours = !self.input.modulus_contours;
+                self.input.store();
+                true
+            }
+            Msg::Heatmap(set) => {
+                let should_render = self.input.heatmap.update(set);
+                self.input.store();
+                should_render
+            }
+            Msg::Tangent(set) => {
+                let should_render = self.input.tangent.update(set);
+                self.input.store();
+                should_render
+            }
+            Msg::Fourier(set) => {
+                let should_render = self.input.fourier.update(set);
+                self.input.store();
+                should_render
+            }
+            Msg::Click(i, j) => {
+                let point = self
+                    .plot_area
+                    .as_ref()
+                    .and_then(|plot_area| plot_area.coordinates((i, j)));
+                // Rounded so that the inputs stay readable
+                let round = |value: f64| (value * 1000.).round() / 1000.;
+                if self.input.mode == Mode::PhasePortrait {
+                    match point {
+                        Some((x, y)) => {
+                            let initial_points = &mut self.input.phase_portrait.initial_points;
+                            initial_points.push((round(x), round(y)));
+                            self.input.store();
+                            true
+                        }
+                        None => false,
+                    }
+                } else if self.input.tangent.enabled {
+                    match point {
+                        Some((x, _)) => {
+                            self.input.tangent.x0 = round(x);
+                            self.input.store();
+                            true
+                        }
+                        None => false,
+                    }
+                } else {
+                    let index = self.input.functions.iter().position(|function_input| {
+                        function_input.show()
+                            && matches!(function_input.kind(), FnInputKind::SlopeField { .. })
+                    });
+                    match (point, index) {
+                        (Some((x, y)), Some(index)) => {
+                            let string = &self.input.functions[index].string;
+                            match slope_field::with_initial_condition(string, (round(x), round(y)))
+                            {
+                                Some(string) => {
+                                    self.set_function(index, string);
+                                    true
+                                }
+                                None => false,
+                            }
+                        }
+                        _ => false,
+                    }
+                }
+            }
+            Msg::DragStart(x, y) => {
+                if self.input.mode == Mode::ThreeD {
+                    self.drag = Some((x, y));
+                }
+                false
+            }
+            Msg::Drag(x, y) => match self.drag {
+                Some((previous_x, previous_y)) => {
+                    self.input.projection.rotate(x - previous_x, y - previous_y);
+                    self.drag = Some((x, y));
+                    true
+                }
+                None => false,
+            },
+            Msg::DragEnd => {
+                if self.drag.take().is_some() {
+                    self.input.store();
+                }
+                false
+            }
 
             Msg::Auxiliary(set) => self.input.update_and_store(set),
-        }
+        };
+        // The input may have changed.
+        self.analysis.get_mut().take();
+        should_render
     }
 
     fn change(&mut self, _props: Self::Properties) -> ShouldRender {
@@ -102,39 +557,69 @@ impl Component for Model {
     }
 
     fn view(&self) -> Html {
+        let analysis = self.analysis();
+        let context = self.input.context(&analysis.dependencies);
         html! {
             <>
-                <div id="canvas-container">
-                    <canvas ref={ self.canvas_ref.clone() } />
+                <div id="canvas-container" ondragover=Callback::from(|e: DragEvent| e.prevent_default()) ondrop=self.link.batch_callback(|e: DragEvent| {
+                    e.prevent_default();
+                    e.data_transfer().and_then(|transfer| transfer.files()).and_then(|files| files.get(0)).map(Msg::LoadFile)
+                })
+                    onmousedown=self.link.callback(|e: MouseEvent| Msg::DragStart(e.client_x(), e.client_y()))
+                    onmousemove=self.link.callback(|e: MouseEvent| Msg::Drag(e.client_x(), e.client_y()))
+                    onmouseup=self.link.callback(|_| Msg::DragEnd)
+                    onmouseleave=self.link.callback(|_| Msg::DragEnd)>
+                    <canvas ref={ self.canvas_ref.clone() } onclick=self.link.callback(|e: MouseEvent| Msg::Click(e.offset_x(), e.offset_y())) />
                 </div>
 
                 <form>
                     <fieldset>
-                    	<legend>{ "Main" }</legend>
-                    	<fieldset>
-	                    	<legend>{ "Functions" }</legend>
-	                    	<p>{ "There are two available formats:"}</p>
-                            <ul>
-                                <li>{ "Analytical: sin(x)" }</li>
-                                <li>{ "Points: [(0, 2), (1, 3.5)]" }</li>
-                            </ul>
-                            { for (0..self.input.functions.len()).map(|index| self.html_fn_input(index)) }
-                            <button type="button" id="add_fn_input" name="add_fn_input" onclick=self.link.callback(|_| Msg::AddFnInput)>{ "Add another function" }</button>
-                        </fieldset>
+                        <legend>{ "Main" }</legend>
+                        <div class="entry">
+                            <label for="mode">{ "Mode" }</label>
+                            <select id="mode" name="mode" onchange=self.link.callback(Msg::Mode)>
+                                { for Mode::ALL.iter().map(|mode| html! {
+                                    <option value=mode.name() selected=*mode == self.input.mode>{ mode.name() }</option>
+                                }) }
+                            </select>
+                        </div>
+                        { match self.input.mode {
+                            Mode::Cartesian => html! {
+                                <>
+                                    { self.html_functions(&analysis, &context) }
+                                    { self.html_key_points(&analysis.key_points) }
+                                    { self.html_tangent(analysis.linearization.as_ref()) }
+                                    { self.html_fourier() }
+                                </>
+                            },
+                            Mode::ThreeD => self.html_functions(&analysis, &context),
+                            Mode::DomainColoring => self.html_complex_function(),
+                            Mode::PhasePortrait => self.html_phase_portrait(),
+                            Mode::Heatmap => html! {
+                                <>
+                                    { self.html_functions(&analysis, &context) }
+                                    { self.html_heatmap() }
+                                </>
+                            },
+                        } }
+                        { self.html_parameters() }
                         <fieldset>
                             <legend>{ "Domain" }</legend>
                             <div class="entry">
-                            	<label for="left">{ "Left" }</label>
-                            	<input type="number" id="left" name="left" value=self.input.domain.0.to_string() max=self.input.domain.1.to_string() step=0.1 onchange=self.link.callback(|x| Msg::Left(x))/>
+                                <label for="left">{ "Left" }</label>
+                                <input type="number" id="left" name="left" value=self.input.domain.0.to_string() max=self.input.domain.1.to_string() step=0.1 onchange=self.link.callback(Msg::Left)/>
                             </div>
                             <div class="entry">
-                            	<label for="right">{ "Right" }</label>
-	                            <input type="number" id="right" name="right" value=self.input.domain.1.to_string() min=self.input.domain.0.to_string() step=0.1 onchange=self.link.callback(|x| Msg::Right(x))/>
+                                <label for="right">{ "Right" }</label>
+                                <input type="number" id="right" name="right" value=self.input.domain.1.to_string() min=self.input.domain.0.to_string() step=0.1 onchange=self.link.callback(Msg::Right)/>
                             </div>
+                            { self.html_y_domain() }
                         </fieldset>
+                        { self.html_range() }
+                        { self.html_projection() }
                     </fieldset>
                     <fieldset>
-                    	<legend>{ "Auxiliary" }</legend>
+                        <legend>{ "Auxiliary" }</legend>
                         { self.html_auxiliary_settings() }
                     </fieldset>
                 </form>
@@ -153,11 +638,34 @@ impl Component for Model {
 }
 
 impl Model {
+    fn set_function(&mut self, index: usize, mut f: String) {
+        log::trace!("Trying to change function index {} to {}", index, f);
+        let (name, kind) = match parse_definition(&f) {
+            Ok((name, k)) => {
+                log::trace!("Identified function input {:?} of kind {:?}", name, k);
+                (name, k)
+            }
+            Err(e) => {
+                log::error!("{}\nInput: {}", e, f);
+                log::warn!("Function changed to default input.");
+                let fn_input = FnInput::default();
+                f = fn_input.string.clone();
+                (None, fn_input.kind().clone())
+            }
+        };
+        self.input.functions[index]
+            .set_name(name)
+            .set_kind(kind)
+            .set_string(f);
+        self.input.sync_parameters();
+        self.input.store();
+    }
+
     fn html_auxiliary_settings(&self) -> Html {
         html! {
-        	<>
-        		<fieldset>
-        			<legend>{ "Plot" }</legend>
+            <>
+                <fieldset>
+                    <legend>{ "Plot" }</legend>
                     <div class="entry">
                         <input type="checkbox" id="title" name="title" checked=self.input.title onchange=self.link.callback(|_| Msg::Auxiliary(Set::Title))/>
                         <label for="title">{ "Title" }</label>
@@ -177,13 +685,18 @@ impl Model {
                         <label for="y_axis">{ "Y-Axis" }</label>
                     </div>
 
+                    <div class="entry">
+                        <label for="slope_field_density">{ "Slope field grid" }</label>
+                        <input type="number" id="slope_field_density" name="slope_field_density" min="2" max="100" value=self.input.slope_field_density.to_string() onchange=self.link.callback(|x| Msg::Auxiliary(Set::SlopeFieldDensity(x)))/>
+                    </div>
+
                     <div class="entry">
                         <label for="quality">{ "Quality" }</label>
                         <input type="range" id="quality" name="quality" min="2" max="1000" value=self.input.quality.to_string() class="slider" onchange=self.link.callback(|x| Msg::Auxiliary(Set::Quality(x)))/>
                     </div>
-        		</fieldset>
-        		<fieldset>
-        			<legend>{ "Canvas" }</legend>
+                </fieldset>
+                <fieldset>
+                    <legend>{ "Canvas" }</legend>
                     <div class="entry">
                         <label for="canvas_width">{ "Width" }</label>
                         <input type="range" id="canvas_width" name="canvas_width" min="5" max="1600" value=self.input.canvas_size.0.to_string() class="slider" onchange=self.link.callback(|x| Msg::Auxiliary(Set::CanvasWidth(x)))/>
@@ -193,127 +706,1915 @@ impl Model {
                         <label for="canvas_height">{ "Height" }</label>
                         <input type="range" id="canvas_height" name="canvas_height" min="5" max="1600" value=self.input.canvas_size.1.to_string() class="slider" onchange=self.link.callback(|x| Msg::Auxiliary(Set::CanvasHeight(x)))/>
                     </div>
-        		</fieldset>
-        	</>
+                </fieldset>
+            </>
         }
     }
 
-    fn html_fn_input(&self, index: usize) -> Html {
-        let fn_input = &self.input.functions[index];
+    fn html_functions(&self, analysis: &Analysis, context: &Context) -> Html {
+        html! {
+            <fieldset>
+                <legend>{ "Functions" }</legend>
+                <p>{ "The available formats are:"}</p>
+                <ul>
+                    <li>{ "Analytical: sin(x) or y = sin(x)" }</li>
+                    <li>{ "Points: [(0, 2), (1, 3.5)], linear" }</li>
+                    <li>{ "Parametric: (cos(t), sin(t)), t in [0, 2pi]" }</li>
+                    <li>{ "Polar: r = 1 + cos(theta)" }</li>
+                    <li>{ "Piecewise: x < 0 ? -x : x^2" }</li>
+                    <li>{ "Implicit: x^2 + y^2 = 1" }</li>
+                    <li>{ "Function of y: x = y^2 - 1" }</li>
+                    <li>{ "Inequality: y > x && y < 2x" }</li>
+                    <li>{ "Slope field: y' = x - y, [(0, 1)], where clicking the plot adds initial conditions" }</li>
+                    <li>{ "Initial value problem: y'' = -y + sin(x), y(0) = 1, y'(0) = 0" }</li>
+                    <li>{ "Sample: [1.2, 3.4, 2.2, 2.9], drawn as a histogram, ECDF or kernel density estimate" }</li>
+                </ul>
+                <p>{ "Functions of x can be named, as in f(x) = sin(x), and called by other functions, as in f(x)^2 + 1." }</p>
+                <p>{ "Other variables, as a and b in a*sin(b*x), are parameters set with sliders." }</p>
+                { for (0..self.input.functions.len()).map(|index| self.html_fn_input(index, analysis, context)) }
+                <button type="button" id="add_fn_input" name="add_fn_input" onclick=self.link.callback(|_| Msg::AddFnInput)>{ "Add another function" }</button>
+                { self.html_data_file() }
+            </fieldset>
+        }
+    }
 
-        let label = format!("fn_input_{}", index);
+    fn html_key_points(&self, key_points: &[KeyPoint]) -> Html {
+        html! {
+            <fieldset>
+                <legend>{ "Key points" }</legend>
+                <div class="entry">
+                    <input type="checkbox" id="key_points" name="key_points" checked=self.input.key_points onchange=self.link.callback(|_| Msg::Auxiliary(Set::KeyPoints))/>
+                    <label for="key_points">{ "Mark zeros, extrema and intersections" }</label>
+                </div>
+                <ul>
+                    { for key_points.iter().map(|key_point| html! {
+                        <li>{ format!("{}: {} at ({:.6}, {:.6})", key_point.functions, key_point.kind.name(), key_point.point.0, key_point.point.1) }</li>
+                    }) }
+                </ul>
+            </fieldset>
+        }
+    }
 
-        let fn_string = fn_input.string.clone();
+    fn html_tangent(&self, linearization: Option<&Linearization>) -> Html {
+        let tangent = &self.input.tangent;
+        let equation = match linearization {
+            Some(linearization) => linearization.equation(),
+            None => "The function has no tangent there.".to_string(),
+        };
+        html! {
+            <fieldset>
+                <legend>{ "Tangent line" }</legend>
+                <div class="entry">
+                    <input type="checkbox" id="tangent" name="tangent" checked=tangent.enabled onchange=self.link.callback(|_| Msg::Tangent(SetTangent::Enabled))/>
+                    <label for="tangent">{ "Draw the tangent line, where clicking the plot sets the point" }</label>
+                </div>
+                <div class="entry">
+                    <label for="tangent_function">{ "Function" }</label>
+                    <select id="tangent_function" name="tangent_function" disabled=!tangent.enabled onchange=self.link.callback(|data| Msg::Tangent(SetTangent::Function(data)))>
+                        { for self.input.functions.iter().enumerate().filter(|(_, function_input)| function_input.kind().is_callable()).map(|(index, function_input)| html! {
+                            <option value=index.to_string() selected=index == tangent.function>{ function_input.name().unwrap_or(&function_input.string) }</option>
+                        }) }
+                    </select>
+                </div>
+                <div class="entry">
+                    <label for="x0">{ "x0" }</label>
+                    <input type="number" id="x0" name="x0" step="any" value=tangent.x0.to_string() disabled=!tangent.enabled onchange=self.link.callback(|x| Msg::Tangent(SetTangent::X0(x)))/>
+                </div>
+                <div class="entry">
+                    <input type="checkbox" id="normal" name="normal" checked=tangent.normal disabled=!tangent.enabled onchange=self.link.callback(|_| Msg::Tangent(SetTangent::Normal))/>
+                    <label for="normal">{ "Normal line" }</label>
+                    <input type="checkbox" id="osculating_circle" name="osculating_circle" checked=tangent.osculating_circle disabled=!tangent.enabled onchange=self.link.callback(|_| Msg::Tangent(SetTangent::OsculatingCircle))/>
+                    <label for="osculating_circle">{ "Osculating circle" }</label>
+                </div>
+                { if tangent.enabled { html! { <p>{ equation }</p> } } else { html! {} } }
+            </fieldset>
+        }
+    }
 
+    fn html_fourier(&self) -> Html {
+        let fourier = &self.input.fourier;
         html! {
-            <div class="entry">
-                <input type="checkbox" name="function_toogler" checked=fn_input.show() onchange=self.link.callback(move |_| Msg::ToggleFunction(index))/>
-                <input type="text" id={ label.clone() } name={ label } autofocus=true value=fn_string onchange=self.link.callback(move |f| Msg::Function(index, f))/>
-            </div>
+            <fieldset>
+                <legend>{ "Fourier transform" }</legend>
+                <div class="entry">
+                    <input type="checkbox" id="fourier" name="fourier" checked=fourier.enabled onchange=self.link.callback(|_| Msg::Fourier(SetFourier::Enabled))/>
+                    <label for="fourier">{ "Draw the magnitude and phase spectra under the plot" }</label>
+                </div>
+                <div class="entry">
+                    <label for="fourier_function">{ "Function" }</label>
+                    <select id="fourier_function" name="fourier_function" disabled=!fourier.enabled onchange=self.link.callback(|data| Msg::Fourier(SetFourier::Function(data)))>
+                        { for self.input.functions.iter().enumerate().filter(|(_, function_input)| function_input.kind().is_callable()).map(|(index, function_input)| html! {
+                            <option value=index.to_string() selected=index == fourier.function>{ function_input.name().unwrap_or(&function_input.string) }</option>
+                        }) }
+                    </select>
+                </div>
+                <p>{ "Functions are sampled on the grid of the plot, and points uniformly between the first and the last one." }</p>
+            </fieldset>
         }
     }
 
-    fn plot(&self, backend: CanvasBackend) {
-        let root = backend.into_drawing_area();
-        root.fill(&WHITE).unwrap();
+    fn html_y_domain(&self) -> Html {
+        if !matches!(
+            self.input.mode,
+            Mode::ThreeD | Mode::Heatmap | Mode::PhasePortrait
+        ) {
+            return html! {};
+        }
+        html! {
+            <>
+                <div class="entry">
+                    <label for="front">{ "Front (y)" }</label>
+                    <input type="number" id="front" name="front" value=self.input.y_domain.0.to_string() max=self.input.y_domain.1.to_string() step=0.1 onchange=self.link.callback(Msg::Front)/>
+                </div>
+                <div class="entry">
+                    <label for="back">{ "Back (y)" }</label>
+                    <input type="number" id="back" name="back" value=self.input.y_domain.1.to_string() min=self.input.y_domain.0.to_string() step=0.1 onchange=self.link.callback(Msg::Back)/>
+                </div>
+            </>
+        }
+    }
 
-        let grid =
-            itertools_num::linspace(self.input.domain.0, self.input.domain.1, self.input.quality);
+    /// Vertical range of the plot, which is not used by the phase portrait.
+    fn html_range(&self) -> Html {
+        if self.input.mode == Mode::PhasePortrait {
+            return html! {};
+        }
+        html! {
+            <fieldset>
+                <legend>{ match self.input.mode {
+                    Mode::ThreeD => "Range of z",
+                    Mode::Heatmap => "Range of the colors",
+                    _ => "Range",
+                } }</legend>
+                <div class="entry">
+                    <input type="checkbox" id="auto_range" name="auto_range" checked=self.input.auto_range onchange=self.link.callback(|_| Msg::AutoRange)/>
+                    <label for="auto_range">{ "Automatic" }</label>
+                </div>
+                <div class="entry">
+                    <label for="bottom">{ "Bottom" }</label>
+                    <input type="number" id="bottom" name="bottom" value=self.input.range.0.to_string() max=self.input.range.1.to_string() step=0.1 disabled=self.input.auto_range onchange=self.link.callback(Msg::Bottom)/>
+                </div>
+                <div class="entry">
+                    <label for="top">{ "Top" }</label>
+                    <input type="number" id="top" name="top" value=self.input.range.1.to_string() min=self.input.range.0.to_string() step=0.1 disabled=self.input.auto_range onchange=self.link.callback(Msg::Top)/>
+                </div>
+            </fieldset>
+        }
+    }
 
-        let mut values_collection = vec![];
-        let mut overall_min = f64::INFINITY;
-        let mut overall_max = f64::NEG_INFINITY;
+    fn html_projection(&self) -> Html {
+        if self.input.mode != Mode::ThreeD {
+            return html! {};
+        }
+        let projection = &self.input.projection;
+        html! {
+            <fieldset>
+                <legend>{ "View" }</legend>
+                <p>{ "Drag the plot to rotate it." }</p>
+                <div class="entry">
+                    <label for="yaw">{ "Yaw" }</label>
+                    <input type="range" id="yaw" name="yaw" min="-3.2" max="3.2" step="0.01" value=projection.yaw.to_string() class="slider" onchange=self.link.callback(|x| Msg::Auxiliary(Set::Yaw(x)))/>
+                </div>
+                <div class="entry">
+                    <label for="pitch">{ "Pitch" }</label>
+                    <input type="range" id="pitch" name="pitch" min="-1.57" max="1.57" step="0.01" value=projection.pitch.to_string() class="slider" onchange=self.link.callback(|x| Msg::Auxiliary(Set::Pitch(x)))/>
+                </div>
+                <div class="entry">
+                    <label for="scale">{ "Scale" }</label>
+                    <input type="range" id="scale" name="scale" min="0.1" max="2" step="0.01" value=projection.scale.to_string() class="slider" onchange=self.link.callback(|x| Msg::Auxiliary(Set::Scale(x)))/>
+                </div>
+            </fieldset>
+        }
+    }
 
-        if self
-            .input
-            .functions
-            .iter()
-            .all(|function_input| !function_input.show())
-        {
-            log::trace!("There is no function to plot.");
-            overall_min = -1.;
-            log::warn!("min value changed to {}", overall_min);
-            overall_max = 1.;
-            log::warn!("max value changed to {}", overall_max);
-        } else {
-            for function_input in &self.input.functions {
-                if function_input.show() {
-                    log::trace!("Computing values for function {:?}", function_input);
-                    match function_input.kind() {
-                        FnInputKind::Analytical { expression, .. } => {
-                            let values: Vec<f64> = grid
-                                .clone()
-                                .map(|x| expression.eval(&[x]).unwrap())
-                                .collect();
-                            let (min, max) = values.iter().minmax().into_option().unwrap();
-                            overall_min = min.min(overall_min);
-                            overall_max = max.max(overall_max);
-                            values_collection.push(values);
-                        }
-                        FnInputKind::Points { spline } => {
-                            let values: Vec<f64> = grid
-                                .clone()
-                                .map(|x| spline.clamped_sample(x).unwrap())
-                                .collect();
-                            let (min, max) = values.iter().minmax().into_option().unwrap();
-                            overall_min = min.min(overall_min);
-                            overall_max = max.max(overall_max);
-                            values_collection.push(values);
-                        }
+    fn html_heatmap(&self) -> Html {
+        let heatmap = &self.input.heatmap;
+        html! {
+            <fieldset>
+                <legend>{ "Heatmap" }</legend>
+                <p>{ "Shows the first function of x and y, such as x^2 + y^2 or z = sin(x*y)." }</p>
+                <div class="entry">
+                    <label for="colormap">{ "Colormap" }</label>
+                    <select id="colormap" name="colormap" onchange=self.link.callback(|data| Msg::Heatmap(SetHeatmap::Colormap(data)))>
+                        { for Colormap::ALL.iter().map(|colormap| html! {
+                            <option value=colormap.name() selected=*colormap == heatmap.colormap>{ colormap.name() }</option>
+                        }) }
+                    </select>
+                </div>
+                <div class="entry">
+                    <label for="levels">{ "Iso-lines at" }</label>
+                    <input type="text" id="levels" name="levels" placeholder="0.5, 1, 2" value=heatmap.levels_string() onchange=self.link.callback(|data| Msg::Heatmap(SetHeatmap::Levels(data)))/>
+                </div>
+            </fieldset>
+        }
+    }
+
+    fn html_phase_portrait(&self) -> Html {
+        let phase_portrait = &self.input.phase_portrait;
+        html! {
+            <fieldset>
+                <legend>{ "Autonomous system" }</legend>
+                <p>{ "System of the form x' = y, y' = -sin(x), which can call the named functions. Click the plot to draw the trajectory through a point. Fixed points are labeled with their kind, found from the Jacobian." }</p>
+                <div class="entry">
+                    <label for="phase_system">{ "System" }</label>
+                    <input type="text" id="phase_system" name="phase_system" value=phase_portrait.text.clone() onchange=self.link.callback(Msg::PhaseSystem)/>
+                </div>
+                <div class="entry">
+                    <span>{ format!("{} trajectories", phase_portrait.initial_points.len()) }</span>
+                    <button type="button" disabled=phase_portrait.initial_points.is_empty() onclick=self.link.callback(|_| Msg::ClearTrajectories)>{ "Clear" }</button>
+                </div>
+            </fieldset>
+        }
+    }
+
+    fn html_complex_function(&self) -> Html {
+        let error = match &self.complex_function_error {
+            Some(error) => html! { <span class="error">{ error }</span> },
+            None => html! {},
+        };
+        html! {
+            <fieldset>
+                <legend>{ "Complex function" }</legend>
+                <p>{ "Function of z, such as (z^2 - 1)/(z^2 + 2i). The hue shows the argument of the value and the lightness its modulus." }</p>
+                <div class="entry">
+                    <label for="complex_function">{ "f(z)" }</label>
+                    <input type="text" id="complex_function" name="complex_function" value=self.input.complex_function.text().to_string() onchange=self.link.callback(Msg::ComplexFunction)/>
+                    { error }
+                </div>
+                <div class="entry">
+                    <input type="checkbox" id="modulus_contours" name="modulus_contours" checked=self.input.modulus_contours onchange=self.link.callback(|_| Msg::ModulusContours)/>
+                    <label for="modulus_contours">{ "Modulus contours" }</label>
+                </div>
+            </fieldset>
+        }
+    }
+
+    fn html_data_file(&self) -> Html {
+        let columns = match &self.data_file {
+            Some(data_file) => {
+                let x_options = data_file.headers.iter().enumerate().map(|(column, header)| html! {
+                    <option value=column.to_string() selected=column == data_file.x_column>{ header }</option>
+                });
+                let y_options = data_file.headers.iter().enumerate().map(|(column, header)| {
+                    let label = format!("y_column_{}", column);
+                    html! {
+                        <>
+                            <input type="checkbox" id={ label.clone() } checked=data_file.y_columns.contains(&column) onchange=self.link.callback(move |_| Msg::DataFile(SetDataFile::ToggleYColumn(column)))/>
+                            <label for={ label }>{ header }</label>
+                        </>
                     }
+                });
+                html! {
+                    <>
+                        <div class="entry">
+                            <span>{ &data_file.name }</span>
+                            <label for="x_column">{ "x" }</label>
+                            <select id="x_column" name="x_column" onchange=self.link.callback(|data| Msg::DataFile(SetDataFile::XColumn(data)))>
+                                { for x_options }
+                            </select>
+                        </div>
+                        <div class="entry">
+                            <span>{ "y" }</span>
+                            { for y_options }
+                        </div>
+                        <div class="entry">
+                            <button type="button" onclick=self.link.callback(|_| Msg::AddDataFile)>{ "Add columns" }</button>
+                            <button type="button" onclick=self.link.callback(|_| Msg::AddDataFileSamples)>{ "Add y columns as samples" }</button>
+                            <button type="button" onclick=self.link.callback(|_| Msg::DiscardDataFile)>{ "Cancel" }</button>
+                        </div>
+                    </>
                 }
             }
+            None => html! {},
+        };
+        let error = match &self.data_file_error {
+            Some(error) => html! { <span class="error">{ error }</span> },
+            None => html! {},
+        };
 
-            log::trace!(
-                "Num max/min values of the plot: ({}, {})",
-                overall_max,
-                overall_min
-            );
-
-            if !overall_min.is_finite() {
-                log::error!("min value is not real!");
-                overall_min = -1.;
-                log::warn!("min value changed to {}", overall_min);
-            }
-            if !overall_max.is_finite() {
-                log::error!("max value is not real!");
-                overall_max = 1.;
-                log::warn!("max value changed to {}", overall_max);
-            }
+        html! {
+            <>
+                <div class="entry">
+                    <label for="data_file">{ "Data file (CSV, TSV) or drop it on the plot" }</label>
+                    <input type="file" id="data_file" name="data_file" accept=".csv,.tsv,.txt,.dat" onchange=self.link.batch_callback(|data| match data {
+                        ChangeData::Files(files) => files.get(0).map(Msg::LoadFile),
+                        _ => None,
+                    })/>
+                    { error }
+                </div>
+                { columns }
+            </>
         }
+    }
 
-        let mut chart_builder = ChartBuilder::on(&root);
-        if self.input.x_axis {
-            chart_builder.set_label_area_size(LabelAreaPosition::Bottom, 40);
-        }
-        if self.input.y_axis {
-            chart_builder.set_label_area_size(LabelAreaPosition::Left, 40);
+    fn html_parameters(&self) -> Html {
+        if self.input.parameters.is_empty() {
+            return html! {};
         }
-        if self.input.title {
-            chart_builder.caption(self.input.title_string.clone(), ("Arial", 30));
+        html! {
+            <fieldset>
+                <legend>{ "Parameters" }</legend>
+                { for (0..self.input.parameters.len()).map(|index| self.html_parameter(index)) }
+            </fieldset>
         }
+    }
 
-        let delta = overall_max - overall_min;
-        let mut chart = chart_builder
-            .build_cartesian_2d(
-                self.input.domain.0..self.input.domain.1,
-                (overall_min - delta / 100.)..(overall_max + delta / 100.),
+    fn html_parameter(&self, index: usize) -> Html {
+        let parameter = &self.input.parameters[index];
+
+        let label = format!("parameter_{}", index);
+
+        html! {
+            <div class="entry">
+                <label for={ label.clone() }>{ &parameter.name }</label>
+                <input type="range" id={ label.clone() } name={ label } min=parameter.min.to_string() max=parameter.max.to_string() step=parameter.step.to_string() value=parameter.value.to_string() class="slider" oninput=self.link.callback(move |e: InputData| Msg::Parameter(index, SetParameter::Value(e.value)))/>
+                <span>{ parameter.value }</span>
+                <label>{ "Min" }</label>
+                <input type="number" value=parameter.min.to_string() max=parameter.max.to_string() onchange=self.link.callback(move |x| Msg::Parameter(index, SetParameter::Min(x)))/>
+                <label>{ "Max" }</label>
+                <input type="number" value=parameter.max.to_string() min=parameter.min.to_string() onchange=self.link.callback(move |x| Msg::Parameter(index, SetParameter::Max(x)))/>
+                <label>{ "Step" }</label>
+                <input type="number" value=parameter.step.to_string() min="0" onchange=self.link.callback(move |x| Msg::Parameter(index, SetParameter::Step(x)))/>
+            </div>
+        }
+    }
+
+    fn html_fn_input(&self, index: usize, analysis: &Analysis, context: &Context) -> Html {
+        let fn_input = &self.input.functions[index];
+        let dependencies = &analysis.dependencies;
+
+        let label = format!("fn_input_{}", index);
+
+        let fn_string = fn_input.string.clone();
+
+        let error = match dependencies.errors.get(&index) {
+            Some(error) => html! { <span class="error">{ error.to_string() }</span> },
+            None => html! {},
+        };
+
+        let kind_settings = match fn_input.kind() {
+            FnInputKind::Sample { values } => {
+                let distribution = fn_input.distribution;
+                let histogram_id = format!("histogram_{}", index);
+                let ecdf_id = format!("ecdf_{}", index);
+                let kde_id = format!("kde_{}", index);
+                let bandwidth = silverman_bandwidth(values) * distribution.bandwidth_factor;
+                html! {
+                    <>
+                        <input type="checkbox" id={ histogram_id.clone() } checked=distribution.histogram onchange=self.link.callback(move |_| Msg::Distribution(index, SetDistribution::Histogram))/>
+                        <label for={ histogram_id }>{ "Histogram" }</label>
+                        <select name="binning" disabled=!distribution.histogram onchange=self.link.callback(move |data| Msg::Distribution(index, SetDistribution::Binning(data)))>
+                            { for Binning::ALL.iter().map(|option| html! {
+                                <option value=option.name() selected=*option == distribution.binning>{ option.name() }</option>
+                            }) }
+                        </select>
+                        <input type="number" name="bins" title="Bins" min="1" max=MAX_BINS.to_string() value=distribution.bins(values).to_string() disabled=!distribution.histogram || distribution.binning != Binning::Count onchange=self.link.callback(move |x| Msg::Distribution(index, SetDistribution::Bins(x)))/>
+                        <input type="checkbox" id={ ecdf_id.clone() } checked=distribution.ecdf onchange=self.link.callback(move |_| Msg::Distribution(index, SetDistribution::Ecdf))/>
+                        <label for={ ecdf_id }>{ "ECDF" }</label>
+                        <input type="checkbox" id={ kde_id.clone() } checked=distribution.kde onchange=self.link.callback(move |_| Msg::Distribution(index, SetDistribution::Kde))/>
+                        <label for={ kde_id }>{ "Kernel density" }</label>
+                        <input type="range" name="bandwidth_factor" title="Bandwidth relative to Silverman's rule" min="0.1" max="3" step="0.05" value=distribution.bandwidth_factor.to_string() class="slider" disabled=!distribution.kde onchange=self.link.callback(move |x| Msg::Distribution(index, SetDistribution::BandwidthFactor(x)))/>
+                        <span>{ format!("bandwidth = {:.4}", bandwidth) }</span>
+                    </>
+                }
+            }
+            FnInputKind::Points { interpolation, .. } => {
+                let style = &fn_input.point_style;
+                html! {
+                    <>
+                        <select name="interpolation" onchange=self.link.callback(move |data| Msg::Interpolation(index, data))>
+                            { for Interpolation::ALL.iter().map(|option| html! {
+                                <option value=option.name() selected=option == interpolation>{ option.name() }</option>
+                            }) }
+                        </select>
+                        <select name="display_mode" onchange=self.link.callback(move |data| Msg::PointStyle(index, SetPointStyle::Mode(data)))>
+                            { for DisplayMode::ALL.iter().map(|option| html! {
+                                <option value=option.name() selected=*option == style.mode>{ option.name() }</option>
+                            }) }
+                        </select>
+                        <select name="marker_shape" disabled=!style.mode.has_markers() onchange=self.link.callback(move |data| Msg::PointStyle(index, SetPointStyle::Shape(data)))>
+                            { for MarkerShape::ALL.iter().map(|option| html! {
+                                <option value=option.name() selected=*option == style.shape>{ option.name() }</option>
+                            }) }
+                        </select>
+                        <input type="number" name="marker_size" min="1" max="20" value=style.size.to_string() disabled=!style.mode.has_markers() onchange=self.link.callback(move |x| Msg::PointStyle(index, SetPointStyle::Size(x)))/>
+                    </>
+                }
+            }
+            FnInputKind::InitialValueProblem { .. } => {
+                let solver = fn_input.solver;
+                html! {
+                    <>
+                        <select name="method" onchange=self.link.callback(move |data| Msg::Solver(index, SetSolver::Method(data)))>
+                            { for Method::ALL.iter().map(|option| html! {
+                                <option value=option.name() selected=*option == solver.method>{ option.name() }</option>
+                            }) }
+                        </select>
+                        <input type="number" name="tolerance" min="0" step="any" title="Tolerance" value=solver.tolerance.to_string() disabled=solver.method != Method::RungeKutta45 onchange=self.link.callback(move |x| Msg::Solver(index, SetSolver::Tolerance(x)))/>
+                    </>
+                }
+            }
+            _ => html! {},
+        };
+
+        let derivative_settings = if fn_input.kind().is_callable() {
+            let name = fn_input.name().unwrap_or("f");
+            let order = fn_input.derivatives;
+            let descriptions: Vec<String> = match fn_input.derivative_expressions(order) {
+                Some(derivatives) => derivatives
+                    .iter()
+                    .enumerate()
+                    .map(|(k, derivative)| {
+                        format!("{}{}(x) = {}", name, "'".repeat(k + 1), derivative.text())
+                    })
+                    .collect(),
+                None => (1..=order)
+                    .map(|k| format!("{}{} by finite differences", name, "'".repeat(k)))
+                    .collect(),
+            };
+            html! {
+                <>
+                    <input type="number" name="derivatives" title="Derivatives" min="0" max=MAX_DERIVATIVES.to_string() value=order.to_string() onchange=self.link.callback(move |x| Msg::Derivatives(index, x))/>
+                    { for descriptions.into_iter().map(|description| html! {
+                        <span class="derivative">{ description }</span>
+                    }) }
+                </>
+            }
+        } else {
+            html! {}
+        };
+
+        let integral_settings = if fn_input.kind().is_callable() {
+            let integral = fn_input.integral;
+            let antiderivative_id = format!("antiderivative_{}", index);
+            let area_id = format!("area_{}", index);
+            let value = if integral.area && !dependencies.errors.contains_key(&index) {
+                let f = |x: f64| fn_input.kind().eval(context, x);
+                let (value, error) = integrate(
+                    &f,
+                    integral.bounds,
+                    integral.quadrature,
+                    self.input.quality,
+                    AREA_TOLERANCE,
+                );
+                html! { <span class="integral">{ format!("= {} ± {:.1e}", value, error) }</span> }
+            } else {
+                html! {}
+            };
+            html! {
+                <>
+                    <input type="checkbox" id={ antiderivative_id.clone() } checked=integral.antiderivative onchange=self.link.callback(move |_| Msg::Integral(index, SetIntegral::Antiderivative))/>
+                    <label for={ antiderivative_id }>{ "∫ from" }</label>
+                    <input type="number" name="lower_bound" title="Lower bound" step="any" value=integral.lower_bound.to_string() disabled=!integral.antiderivative onchange=self.link.callback(move |x| Msg::Integral(index, SetIntegral::LowerBound(x)))/>
+                    <select name="quadrature" onchange=self.link.callback(move |data| Msg::Integral(index, SetIntegral::Quadrature(data)))>
+                        { for Quadrature::ALL.iter().map(|option| html! {
+                            <option value=option.name() selected=*option == integral.quadrature>{ option.name() }</option>
+                        }) }
+                    </select>
+                    <input type="checkbox" id={ area_id.clone() } checked=integral.area onchange=self.link.callback(move |_| Msg::Integral(index, SetIntegral::Area))/>
+                    <label for={ area_id }>{ "Area between" }</label>
+                    <input type="number" name="area_start" title="Start" step="any" value=integral.bounds.0.to_string() disabled=!integral.area onchange=self.link.callback(move |x| Msg::Integral(index, SetIntegral::Start(x)))/>
+                    <input type="number" name="area_end" title="End" step="any" value=integral.bounds.1.to_string() disabled=!integral.area onchange=self.link.callback(move |x| Msg::Integral(index, SetIntegral::End(x)))/>
+                    { value }
+                </>
+            }
+        } else {
+            html! {}
+        };
+
+        let fit_settings = if matches!(fn_input.kind(), FnInputKind::Points { .. }) {
+            let fit = &fn_input.fit;
+            let fit_id = format!("fit_{}", index);
+            let result = match &analysis.fits[index] {
+                Some(result) => {
+                    let coefficients = result
+                        .coefficients
+                        .iter()
+                        .map(|(name, value)| format!("{} = {:.6}", name, value))
+                        .collect::<Vec<String>>()
+                        .join(", ");
+                    let residuals = result
+                        .residuals
+                        .iter()
+                        .map(|residual| format!("{:.3}", residual))
+                        .collect::<Vec<String>>()
+                        .join(", ");
+                    html! {
+                        <>
+                            <span class="fit">{ format!("{}, R² = {:.6}", coefficients, result.r_squared) }</span>
+                            <span class="fit">{ format!("Residuals: {}", residuals) }</span>
+                            <button type="button" onclick=self.link.callback(move |_| Msg::AddFit(index))>{ "Add as function" }</button>
+                        </>
+                    }
+                }
+                None if fit.enabled => {
+                    html! { <span class="error">{ "The model can not be fitted to the data." }</span> }
+                }
+                None => html! {},
+            };
+            html! {
+                <>
+                    <input type="checkbox" id={ fit_id.clone() } checked=fit.enabled onchange=self.link.callback(move |_| Msg::Fit(index, SetFit::Enabled))/>
+                    <label for={ fit_id }>{ "Fit" }</label>
+                    <select name="fit_model" disabled=!fit.enabled onchange=self.link.callback(move |data| Msg::Fit(index, SetFit::Model(data)))>
+                        { for FitModel::ALL.iter().map(|option| html! {
+                            <option value=option.name() selected=*option == fit.model>{ option.name() }</option>
+                        }) }
+                    </select>
+                    <input type="number" name="fit_degree" title="Degree" min="0" max=MAX_FIT_DEGREE.to_string() value=fit.degree.to_string() disabled=!fit.enabled || fit.model != FitModel::Polynomial onchange=self.link.callback(move |x| Msg::Fit(index, SetFit::Degree(x)))/>
+                    <input type="text" name="fit_custom" title="Model, whose variables other than x are fitted" value=fit.custom.clone() disabled=!fit.enabled || fit.model != FitModel::Custom onchange=self.link.callback(move |s| Msg::Fit(index, SetFit::Custom(s)))/>
+                    { result }
+                </>
+            }
+        } else {
+            html! {}
+        };
+
+        let taylor_settings = if matches!(fn_input.kind(), FnInputKind::Analytical { .. }) {
+            let settings = fn_input.taylor;
+            let taylor_id = format!("taylor_{}", index);
+            let polynomial = if settings.enabled {
+                match settings.coefficients(fn_input, context) {
+                    Some(coefficients) => format!(
+                        "T(x) = {}",
+                        taylor::polynomial_string(&coefficients, settings.center)
+                    ),
+                    None => "Its derivatives can not be computed symbolically.".to_string(),
+                }
+            } else {
+                String::new()
+            };
+            html! {
+                <>
+                    <input type="checkbox" id={ taylor_id.clone() } checked=settings.enabled onchange=self.link.callback(move |_| Msg::Taylor(index, SetTaylor::Enabled))/>
+                    <label for={ taylor_id }>{ "Taylor polynomial at" }</label>
+                    <input type="number" name="taylor_center" title="Center" step="any" value=settings.center.to_string() disabled=!settings.enabled onchange=self.link.callback(move |x| Msg::Taylor(index, SetTaylor::Center(x)))/>
+                    <input type="range" name="taylor_order" title="Order" min="0" max=MAX_TAYLOR_ORDER.to_string() value=settings.order.to_string() class="slider" disabled=!settings.enabled oninput=self.link.callback(move |e: InputData| Msg::Taylor(index, SetTaylor::Order(e.value)))/>
+                    <span>{ format!("of order {}, shaded where the error is below", settings.order.min(MAX_TAYLOR_ORDER)) }</span>
+                    <input type="number" name="taylor_tolerance" title="Tolerance" min="0" step="any" value=settings.tolerance.to_string() disabled=!settings.enabled onchange=self.link.callback(move |x| Msg::Taylor(index, SetTaylor::Tolerance(x)))/>
+                    <span class="taylor">{ polynomial }</span>
+                </>
+            }
+        } else {
+            html! {}
+        };
+
+        let fourier_settings = if matches!(fn_input.kind(), FnInputKind::Analytical { .. }) {
+            html! {
+                <>
+                    <label>{ "Fourier series of order" }</label>
+                    <input type="number" name="fourier_series" title="Order of the Fourier series, zero for none" min="0" max=MAX_FOURIER_ORDER.to_string() value=fn_input.fourier_series.to_string() onchange=self.link.callback(move |x| Msg::FourierSeries(index, x))/>
+                </>
+            }
+        } else {
+            html! {}
+        };
+
+        html! {
+            <div class="entry">
+                <input type="checkbox" name="function_toogler" checked=fn_input.show() onchange=self.link.callback(move |_| Msg::ToggleFunction(index))/>
+                <input type="text" id={ label.clone() } name={ label } autofocus=true value=fn_string onchange=self.link.callback(move |f| Msg::Function(index, f))/>
+                { kind_settings }
+                { derivative_settings }
+                { integral_settings }
+                { taylor_settings }
+                { fourier_settings }
+                { fit_settings }
+                { error }
+            </div>
+        }
+    }
+
+    /// Analysis of the input, computed unless it already was since the last update.
+    fn analysis(&self) -> Ref<'_, Analysis> {
+        if self.analysis.borrow().is_none() {
+            let analysis = self.analyze();
+            *self.analysis.borrow_mut() = Some(analysis);
+        }
+        Ref::map(self.analysis.borrow(), |analysis| {
+            analysis.as_ref().unwrap()
+        })
+    }
+
+    fn analyze(&self) -> Analysis {
+        let dependencies = self.input.dependencies();
+        let context = self.input.context(&dependencies);
+        let cartesian = self.input.mode == Mode::Cartesian;
+        let key_points = if cartesian {
+            self.key_points(&dependencies, &context)
+        } else {
+            vec![]
+        };
+        let linearization = if cartesian && self.input.tangent.enabled {
+            self.linearization(&dependencies, &context)
+        } else {
+            None
+        };
+        let fits = (0..self.input.functions.len())
+            .map(|index| self.fit(index, &context))
+            .collect();
+        Analysis {
+            key_points,
+            linearization,
+            fits,
+            dependencies,
+        }
+    }
+
+    /// Zeros, local extrema and pairwise intersections of the visible functions of `x`
+    /// inside the domain, bracketed on the grid of the plot and refined by Brent's method.
+    fn key_points(&self, dependencies: &Dependencies, context: &Context) -> Vec<KeyPoint> {
+        if !self.input.key_points {
+            return vec![];
+        }
+        let functions: Vec<(String, &FnInputKind)> = self
+            .input
+            .functions
+            .iter()
+            .enumerate()
+            .filter(|(index, function_input)| {
+                function_input.show()
+                    && function_input.kind().is_callable()
+                    && !dependencies.errors.contains_key(index)
+            })
+            .map(|(_, function_input)| {
+                let label = function_input
+                    .name()
+                    .unwrap_or(&function_input.string)
+                    .to_string();
+                (label, function_input.kind())
+            })
+            .collect();
+        let xs: Vec<f64> =
+            itertools_num::linspace(self.input.domain.0, self.input.domain.1, self.input.quality)
+                .collect();
+
+        let mut key_points = vec![];
+        for (label, kind) in &functions {
+            let f = |x: f64| kind.eval(context, x);
+            key_points.extend(zeros(&f, &xs).into_iter().map(|x| KeyPoint {
+                kind: KeyPointKind::Zero,
+                functions: label.clone(),
+                point: (x, 0.),
+            }));
+            key_points.extend(extrema(&f, &xs).into_iter().map(|(x, extremum)| KeyPoint {
+                kind: KeyPointKind::Extremum(extremum),
+                functions: label.clone(),
+                point: (x, f(x)),
+            }));
+        }
+        for ((label, kind), (other_label, other)) in functions.iter().tuple_combinations() {
+            let difference = |x: f64| kind.eval(context, x) - other.eval(context, x);
+            key_points.extend(zeros(&difference, &xs).into_iter().map(|x| KeyPoint {
+                kind: KeyPointKind::Intersection,
+                functions: format!("{} and {}", label, other_label),
+                point: (x, kind.eval(context, x)),
+            }));
+        }
+        key_points.retain(|key_point| key_point.point.1.is_finite());
+        key_points
+    }
+
+    /// Value and derivatives at `x0` of the function chosen in the tangent tool, symbolic
+    /// for analytical functions and by central differences otherwise.
+    fn linearization(
+        &self,
+        dependencies: &Dependencies,
+        context: &Context,
+    ) -> Option<Linearization> {
+        let tangent = &self.input.tangent;
+        if dependencies.errors.contains_key(&tangent.function) {
+            return None;
+        }
+        let function = self.input.functions.get(tangent.function)?;
+        let kind = function.kind();
+        if !kind.is_callable() {
+            return None;
+        }
+        let x0 = tangent.x0;
+        let (slope, second_derivative) = match function.derivative_expressions(2) {
+            Some(derivatives) => (
+                derivatives[0].eval(context, &[("x", x0)]),
+                derivatives[1].eval(context, &[("x", x0)]),
+            ),
+            None => {
+                let f = |x: f64| kind.eval(context, x);
+                let h = (self.input.domain.1 - self.input.domain.0) * 1e-3;
+                (derivative(&f, x0, 1, h), derivative(&f, x0, 2, h))
+            }
+        };
+        let point = (x0, kind.eval(context, x0));
+        if !point.1.is_finite() || !slope.is_finite() {
+            return None;
+        }
+        Some(Linearization {
+            point,
+            slope,
+            second_derivative,
+        })
+    }
+
+    /// Least-squares fit of the function input `index`, if it is enabled and the
+    /// input is point data.
+    fn fit(&self, index: usize, context: &Context) -> Option<FitResult> {
+        let function_input = &self.input.functions[index];
+        let data = match function_input.kind() {
+            FnInputKind::Points {
+                spline,
+                interpolation,
+            } if function_input.fit.enabled => points::data(spline, *interpolation),
+            _ => return None,
+        };
+        function_input.fit.fit(&data, context)
+    }
+
+    /// Samples of the function chosen in the Fourier panel and their spacing, taken
+    /// over the domain for functions and between the first and the last data point
+    /// for points, with the period as the length of the interval.
+    fn fourier_samples(
+        &self,
+        dependencies: &Dependencies,
+        context: &Context,
+    ) -> Option<(Vec<f64>, f64)> {
+        let fourier = &self.input.fourier;
+        if dependencies.errors.contains_key(&fourier.function) {
+            return None;
+        }
+        let kind = self.input.functions.get(fourier.function)?.kind();
+        let (start, end) = match kind {
+            FnInputKind::Points {
+                spline,
+                interpolation,
+            } => {
+                let data = points::data(spline, *interpolation);
+                (data.first()?.0, data.last()?.0)
+            }
+            kind if kind.is_callable() => self.input.domain,
+            _ => return None,
+        };
+        let n = self.input.quality;
+        let spacing = (end - start) / n as f64;
+        let samples: Vec<f64> = (0..n)
+            .map(|j| kind.eval(context, start + j as f64 * spacing))
+            .collect();
+        if spacing <= 0. || !samples.iter().all(|sample| sample.is_finite()) {
+            log::error!("The spectrum needs finite samples over an interval.");
+            return None;
+        }
+        Some((samples, spacing))
+    }
+
+    /// Draws the magnitude and the phase spectra of `samples` side by side.
+    fn plot_spectrum(
+        &self,
+        area: &DrawingArea<CanvasBackend, Shift>,
+        samples: &[f64],
+        spacing: f64,
+    ) {
+        let spectrum = spectrum(samples, spacing);
+        let highest = spectrum.last().map_or(1., |(frequency, _, _)| *frequency);
+        let largest = spectrum
+            .iter()
+            .map(|(_, magnitude, _)| *magnitude)
+            .fold(0., f64::max)
+            .max(1e-12);
+        let (width, _) = area.dim_in_pixel();
+        let (magnitude_area, phase_area) = area.split_horizontally(width / 2);
+
+        let mut magnitude_chart = self
+            .untitled_chart_builder(&magnitude_area)
+            .caption("Magnitude", ("Arial", 16))
+            .build_cartesian_2d(0. ..highest, 0. ..largest * 1.05)
+            .unwrap();
+        self.draw_mesh(&mut magnitude_chart);
+        magnitude_chart
+            .draw_series(spectrum.iter().map(|(frequency, magnitude, _)| {
+                PathElement::new(vec![(*frequency, 0.), (*frequency, *magnitude)], BLUE)
+            }))
+            .unwrap();
+
+        let pi = core::f64::consts::PI;
+        let mut phase_chart = self
+            .untitled_chart_builder(&phase_area)
+            .caption("Phase", ("Arial", 16))
+            .build_cartesian_2d(0. ..highest, -pi..pi)
+            .unwrap();
+        self.draw_mesh(&mut phase_chart);
+        phase_chart
+            .draw_series(
+                spectrum
+                    .iter()
+                    // The phase of negligible terms is only rounding noise.
+                    .filter(|(_, magnitude, _)| *magnitude > largest * 1e-9)
+                    .map(|(frequency, _, phase)| {
+                        Circle::new((*frequency, *phase), 2, BLUE.filled())
+                    }),
             )
             .unwrap();
+    }
+
+    /// Draws the plot of the current mode, returning the plotting area if it is in the plane.
+    fn plot(&self, backend: CanvasBackend) -> Option<PlotArea> {
+        let root = backend.into_drawing_area();
+        root.fill(&WHITE).unwrap();
+        let analysis = self.analysis();
+        let dependencies = &analysis.dependencies;
+        let context = self.input.context(dependencies);
+        match self.input.mode {
+            Mode::Cartesian => {
+                let samples = Some(&self.input.fourier)
+                    .filter(|fourier| fourier.enabled)
+                    .and_then(|_| self.fourier_samples(dependencies, &context));
+                return Some(match samples {
+                    Some((samples, spacing)) => {
+                        let (_, height) = root.dim_in_pixel();
+                        let split = (f64::from(height) * (1. - SPECTRUM_HEIGHT)) as u32;
+                        let (upper, lower) = root.split_vertically(split);
+                        self.plot_spectrum(&lower, &samples, spacing);
+                        self.plot_cartesian(&upper, &analysis, &context)
+                    }
+                    None => self.plot_cartesian(&root, &analysis, &context),
+                });
+            }
+            Mode::DomainColoring => self.plot_domain_coloring(&root, &context),
+            Mode::ThreeD => self.plot_3d(&root, dependencies, &context),
+            Mode::Heatmap => self.plot_heatmap(&root, dependencies, &context),
+            Mode::PhasePortrait => return Some(self.plot_phase_portrait(&root, &context)),
+        }
+        None
+    }
+
+    /// Chart over the domain and the vertical range `(bottom, top)`, with the
+    /// title and axes chosen by the user.
+    fn chart<'a>(
+        &self,
+        root: &'a DrawingArea<CanvasBackend, Shift>,
+        (bottom, top): (f64, f64),
+    ) -> Chart<'a> {
+        self.chart_builder(root)
+            .build_cartesian_2d(self.input.domain.0..self.input.domain.1, bottom..top)
+            .unwrap()
+    }
+
+    fn chart_builder<'a, 'b>(
+        &self,
+        root: &'a DrawingArea<CanvasBackend, Shift>,
+    ) -> ChartBuilder<'a, 'b, CanvasBackend> {
+        let mut chart_builder = self.untitled_chart_builder(root);
+        if self.input.title {
+            chart_builder.caption(self.input.title_string.clone(), ("Arial", 30));
+        }
+        chart_builder
+    }
+
+    fn untitled_chart_builder<'a, 'b>(
+        &self,
+        root: &'a DrawingArea<CanvasBackend, Shift>,
+    ) -> ChartBuilder<'a, 'b, CanvasBackend> {
+        let mut chart_builder = ChartBuilder::on(root);
+        if self.input.x_axis {
+            chart_builder.set_label_area_size(LabelAreaPosition::Bottom, 40);
+        }
+        if self.input.y_axis {
+            chart_builder.set_label_area_size(LabelAreaPosition::Left, 40);
+        }
+        chart_builder
+    }
 
+    fn draw_mesh(&self, chart: &mut Chart) {
         let mut mesh_style = chart.configure_mesh();
         if !self.input.mesh {
             mesh_style.disable_mesh();
         }
         mesh_style.draw().unwrap();
+    }
 
-        if !values_collection.is_empty() {
-            for values in values_collection {
+    /// Colors each pixel by the value of the complex function at the corresponding point,
+    /// the real part along the horizontal axis and the imaginary part along the vertical one.
+    ///
+    /// The colors are put on the canvas as a single image, since the canvas backend
+    /// draws pixels, and bitmaps, one rectangle at a time.
+    fn plot_domain_coloring(&self, root: &DrawingArea<CanvasBackend, Shift>, context: &Context) {
+        let (bottom, top) = if self.input.auto_range {
+            self.input.domain
+        } else {
+            self.input.range
+        };
+        let mut chart = self.chart(root, (bottom, top));
+
+        let area = chart.plotting_area();
+        let (width, height) = area.dim_in_pixel();
+        let (left, right) = self.input.domain;
+        let mut pixels = Vec::with_capacity(4 * width as usize * height as usize);
+        for j in 0..height {
+            let im = top - (j as f64 + 0.5) / height as f64 * (top - bottom);
+            for i in 0..width {
+                let re = left + (i as f64 + 0.5) / width as f64 * (right - left);
+                let value = self
+                    .input
+                    .complex_function
+                    .eval(context, Complex::new(re, im));
+                let (hue, saturation, lightness) = color(value, self.input.modulus_contours);
+                let (r, g, b) = HSLColor(hue, saturation, lightness).to_backend_color().rgb;
+                pixels.extend_from_slice(&[r, g, b, u8::MAX]);
+            }
+        }
+        if let Some(canvas) = self.canvas_ref.cast::<HtmlCanvasElement>() {
+            put_image(&canvas, &pixels, width, area.get_base_pixel());
+        }
+        self.draw_mesh(&mut chart);
+    }
+
+    /// Draws the vector field of the autonomous system, its nullclines, the trajectories
+    /// through the chosen points and the fixed points with their kind.
+    fn plot_phase_portrait(
+        &self,
+        root: &DrawingArea<CanvasBackend, Shift>,
+        context: &Context,
+    ) -> PlotArea {
+        let phase_portrait = &self.input.phase_portrait;
+        let field = |x: f64, y: f64| {
+            let bindings = [("x", x), ("y", y)];
+            (
+                phase_portrait.f.eval(context, &bindings),
+                phase_portrait.g.eval(context, &bindings),
+            )
+        };
+        let domain = self.input.domain;
+        let (bottom, top) = self.input.y_domain;
+
+        let mut chart = self.chart(root, (bottom, top));
+        self.draw_mesh(&mut chart);
+        let plot_area = PlotArea::new(&chart, domain, (bottom, top));
+
+        // Arrows have the same length in pixels whatever their direction.
+        let density = self.input.slope_field_density;
+        let (columns, rows) = &plot_area.pixels;
+        let x_scale = f64::from(columns.end - columns.start) / (domain.1 - domain.0);
+        let y_scale = f64::from(rows.end - rows.start) / (top - bottom);
+        let half_length = 0.35
+            * f64::from((columns.end - columns.start).min(rows.end - rows.start))
+            / density as f64;
+        let mut arrows = vec![];
+        for i in 0..density {
+            let x = domain.0 + (i as f64 + 0.5) / density as f64 * (domain.1 - domain.0);
+            for j in 0..density {
+                let y = bottom + (j as f64 + 0.5) / density as f64 * (top - bottom);
+                let (u, v) = field(x, y);
+                let norm = (u * x_scale).hypot(v * y_scale);
+                if !norm.is_normal() {
+                    continue;
+                }
+                let (dx, dy) = (half_length * u / norm, half_length * v / norm);
+                let head = (x + dx, y + dy);
+                // Sides of the head, turned by a quarter of the way back
+                let side = |sign: f64| {
+                    let (px, py) = (-dy * y_scale / x_scale, dx * x_scale / y_scale);
+                    (
+                        head.0 - 0.5 * dx + sign * 0.3 * px,
+                        head.1 - 0.5 * dy + sign * 0.3 * py,
+                    )
+                };
+                arrows.push(vec![(x - dx, y - dy), head]);
+                arrows.push(vec![side(1.), head, side(-1.)]);
+            }
+        }
+        chart
+            .draw_series(
+                arrows
+                    .into_iter()
+                    .map(|arrow| PathElement::new(arrow, BLACK.mix(0.4))),
+            )
+            .unwrap();
+
+        let resolution = (self.input.quality, self.input.quality);
+        let tolerance = (domain.1 - domain.0) / self.input.quality as f64 * 1e-6;
+        let nullclines = [
+            (
+                Grid::new(|x, y| field(x, y).0, domain, (bottom, top), resolution),
+                BLUE,
+            ),
+            (
+                Grid::new(|x, y| field(x, y).1, domain, (bottom, top), resolution),
+                RED,
+            ),
+        ];
+        for (grid, color) in &nullclines {
+            for polyline in join(&grid.contour(0.), tolerance) {
                 chart
-                    .draw_series(LineSeries::new(grid.clone().zip(values), &BLACK))
+                    .draw_series(DashedLineSeries::new(polyline, 5, 5, color.into()))
                     .unwrap();
             }
         }
+
+        let step = TRAJECTORY_TIME / self.input.quality as f64;
+        for &(x, y) in &phase_portrait.initial_points {
+            let f = |_: f64, point: &[f64]| {
+                let (u, v) = field(point[0], point[1]);
+                vec![u, v]
+            };
+            let trajectory = solve_system(
+                f,
+                (0., &[x, y]),
+                (-TRAJECTORY_TIME, TRAJECTORY_TIME),
+                Method::RungeKutta45,
+                step,
+                1e-6,
+            );
+            chart
+                .draw_series(LineSeries::new(
+                    trajectory
+                        .into_iter()
+                        .map(|(_, point)| (point[0], point[1])),
+                    BLACK,
+                ))
+                .unwrap();
+            chart
+                .draw_series(std::iter::once(Circle::new((x, y), 3, BLACK.filled())))
+                .unwrap();
+        }
+
+        chart
+            .draw_series(
+                fixed_points(field, domain, (bottom, top), 10)
+                    .into_iter()
+                    .map(|(point, kind)| {
+                        EmptyElement::at(point)
+                            + Circle::new((0, 0), 4, RED.filled())
+                            + Text::new(kind.name(), (6, -14), ("Arial", 12))
+                    }),
+            )
+            .unwrap();
+        plot_area
+    }
+
+    /// Colors the plane by the value of the first visible function of `x` and `y`,
+    /// with iso-lines at the chosen levels and a colorbar on the right.
+    fn plot_heatmap(
+        &self,
+        root: &DrawingArea<CanvasBackend, Shift>,
+        dependencies: &Dependencies,
+        context: &Context,
+    ) {
+        let expression = self
+            .input
+            .functions
+            .iter()
+            .enumerate()
+            .filter(|(index, function_input)| {
+                function_input.show() && !dependencies.errors.contains_key(index)
+            })
+            .find_map(|(_, function_input)| match function_input.kind() {
+                FnInputKind::Surface { expression } | FnInputKind::Implicit { expression } => {
+                    Some(expression)
+                }
+                _ => None,
+            });
+        let resolution = self.input.quality.max(2);
+        let grid = expression.map(|expression| {
+            let f = |x: f64, y: f64| expression.eval(context, &[("x", x), ("y", y)]);
+            Grid::new(
+                f,
+                self.input.domain,
+                self.input.y_domain,
+                (resolution, resolution),
+            )
+        });
+        if grid.is_none() {
+            log::warn!("There is no function of x and y to show.");
+        }
+
+        let (bottom, top) = if self.input.auto_range {
+            let values = grid
+                .iter()
+                .flat_map(|grid| grid.values.iter().flatten().copied())
+                .filter(|value| value.is_finite());
+            match values.minmax().into_option() {
+                Some((min, max)) if min < max => (min, max),
+                Some((min, _)) => (min - 1., min + 1.),
+                None => (-1., 1.),
+            }
+        } else {
+            self.input.range
+        };
+        if bottom.partial_cmp(&top) != Some(core::cmp::Ordering::Less) {
+            log::error!("The range of the colors is empty.");
+            return;
+        }
+        let colormap = self.input.heatmap.colormap;
+
+        // The title spans both the plot and the colorbar, which are then aligned.
+        let area = if self.input.title {
+            root.titled(&self.input.title_string, ("Arial", 30))
+                .unwrap()
+        } else {
+            root.clone()
+        };
+        let (width, _) = area.dim_in_pixel();
+        let (plot_area, colorbar_area) =
+            area.split_horizontally(width.saturating_sub(COLORBAR_WIDTH));
+
+        let mut chart = self
+            .untitled_chart_builder(&plot_area)
+            .build_cartesian_2d(
+                self.input.domain.0..self.input.domain.1,
+                self.input.y_domain.0..self.input.y_domain.1,
+            )
+            .unwrap();
+        if let Some(grid) = &grid {
+            // Each pixel takes the color of the nearest sample.
+            let area = chart.plotting_area();
+            let (width, height) = area.dim_in_pixel();
+            let (left, right) = self.input.domain;
+            let (y_bottom, y_top) = self.input.y_domain;
+            let nearest =
+                |t: f64| ((t * (resolution - 1) as f64).round() as usize).min(resolution - 1);
+            let mut pixels = Vec::with_capacity(4 * width as usize * height as usize);
+            for j in 0..height {
+                let y = y_top - (j as f64 + 0.5) / height as f64 * (y_top - y_bottom);
+                let row = &grid.values[nearest((y - y_bottom) / (y_top - y_bottom))];
+                for i in 0..width {
+                    let x = left + (i as f64 + 0.5) / width as f64 * (right - left);
+                    let value = row[nearest((x - left) / (right - left))];
+                    let (r, g, b) = if value.is_finite() {
+                        colormap_color(colormap, value, (bottom, top)).rgb()
+                    } else {
+                        WHITE.rgb()
+                    };
+                    pixels.extend_from_slice(&[r, g, b, u8::MAX]);
+                }
+            }
+            if let Some(canvas) = self.canvas_ref.cast::<HtmlCanvasElement>() {
+                put_image(&canvas, &pixels, width, area.get_base_pixel());
+            }
+        }
+        self.draw_mesh(&mut chart);
+        if let Some(grid) = &grid {
+            let tolerance = (self.input.domain.1 - self.input.domain.0) / resolution as f64 * 1e-6;
+            for &level in &self.input.heatmap.levels {
+                for polyline in join(&grid.contour(level), tolerance) {
+                    chart
+                        .draw_series(std::iter::once(PathElement::new(polyline, BLACK)))
+                        .unwrap();
+                }
+            }
+        }
+
+        let mut colorbar_builder = ChartBuilder::on(&colorbar_area);
+        colorbar_builder
+            .margin_left(10)
+            .set_label_area_size(LabelAreaPosition::Right, 50);
+        if self.input.x_axis {
+            colorbar_builder.set_label_area_size(LabelAreaPosition::Bottom, 40);
+        }
+        let mut colorbar = colorbar_builder
+            .build_cartesian_2d(0f64..1f64, bottom..top)
+            .unwrap();
+        colorbar
+            .configure_mesh()
+            .disable_mesh()
+            .disable_x_axis()
+            .draw()
+            .unwrap();
+        let steps = 100;
+        colorbar
+            .draw_series((0..steps).map(|step| {
+                let start = bottom + (top - bottom) * step as f64 / steps as f64;
+                let end = bottom + (top - bottom) * (step + 1) as f64 / steps as f64;
+                let color = colormap_color(colormap, (start + end) / 2., (bottom, top));
+                Rectangle::new([(0., start), (1., end)], color.filled())
+            }))
+            .unwrap();
+        colorbar
+            .draw_series(
+                self.input
+                    .heatmap
+                    .levels
+                    .iter()
+                    .filter(|level| (bottom..=top).contains(*level))
+                    .map(|&level| PathElement::new(vec![(0., level), (1., level)], BLACK)),
+            )
+            .unwrap();
+    }
+
+    /// Draws the surfaces and curves in space, with `z` along the vertical axis.
+    fn plot_3d(
+        &self,
+        root: &DrawingArea<CanvasBackend, Shift>,
+        dependencies: &Dependencies,
+        context: &Context,
+    ) {
+        let resolution = self.input.quality.min(MAX_SURFACE_RESOLUTION);
+        let mut surfaces = vec![];
+        let mut curves: Vec<Vec<(f64, f64, f64)>> = vec![];
+        for (index, function_input) in self.input.functions.iter().enumerate() {
+            if !function_input.show() || dependencies.errors.contains_key(&index) {
+                continue;
+            }
+            match function_input.kind() {
+                FnInputKind::Surface { expression } => {
+                    let f = |x: f64, y: f64| expression.eval(context, &[("x", x), ("y", y)]);
+                    surfaces.push(Grid::new(
+                        f,
+                        self.input.domain,
+                        self.input.y_domain,
+                        (resolution, resolution),
+                    ));
+                }
+                FnInputKind::Parametric3d { x, y, z, range } => curves.push(
+                    range
+                        .grid(self.input.quality)
+                        .map(|t| {
+                            let bindings = [("t", t)];
+                            (
+                                x.eval(context, &bindings),
+                                y.eval(context, &bindings),
+                                z.eval(context, &bindings),
+                            )
+                        })
+                        .collect(),
+                ),
+                _ => (),
+            }
+        }
+
+        let (bottom, top) = if self.input.auto_range {
+            let heights = surfaces
+                .iter()
+                .flat_map(|grid| grid.values.iter().flatten().copied())
+                .chain(curves.iter().flatten().map(|(_, _, z)| *z))
+                .filter(|z| z.is_finite());
+            match heights.minmax().into_option() {
+                Some((min, max)) if min < max => (min, max),
+                Some((min, _)) => (min - 1., min + 1.),
+                None => (-1., 1.),
+            }
+        } else {
+            self.input.range
+        };
+
+        // In plotters, the second coordinate is the vertical one.
+        let mut chart = self
+            .chart_builder(root)
+            .build_cartesian_3d(
+                self.input.domain.0..self.input.domain.1,
+                bottom..top,
+                self.input.y_domain.0..self.input.y_domain.1,
+            )
+            .unwrap();
+        let projection = self.input.projection;
+        chart.with_projection(|mut builder| {
+            builder.yaw = projection.yaw;
+            builder.pitch = projection.pitch;
+            builder.scale = projection.scale;
+            builder.into_matrix()
+        });
+        chart.configure_axes().draw().unwrap();
+
+        let visible = |z: f64| (bottom..=top).contains(&z);
+        // Quads of all the surfaces with the depth of their corners, drawn from the
+        // farthest to the nearest so that nearer ones hide those behind them.
+        let mut quads = vec![];
+        for grid in &surfaces {
+            for j in 0..grid.y.len().saturating_sub(1) {
+                for i in 0..grid.x.len().saturating_sub(1) {
+                    let corners = [(i, j), (i + 1, j), (i + 1, j + 1), (i, j + 1)]
+                        .map(|(i, j)| (grid.x[i], grid.values[j][i], grid.y[j]));
+                    if corners.iter().all(|(_, z, _)| visible(*z)) {
+                        let depth: i32 = corners
+                            .iter()
+                            .map(|(x, z, y)| chart.as_coord_spec().projected_depth(x, z, y))
+                            .sum();
+                        quads.push((depth, corners));
+                    }
+                }
+            }
+        }
+        quads.sort_by_key(|(depth, _)| core::cmp::Reverse(*depth));
+        chart
+            .draw_series(quads.into_iter().map(|(_, corners)| {
+                let height = corners.iter().map(|(_, z, _)| z).sum::<f64>() / 4.;
+                let level = (height - bottom) / (top - bottom);
+                let color = HSLColor(2. / 3. * (1. - level), 0.7, 0.5);
+                Polygon::new(corners.to_vec(), color.mix(0.8).filled())
+            }))
+            .unwrap();
+        for points in curves {
+            // Non-finite values split the curve
+            for path in points.split(|(x, y, z)| !(x.is_finite() && y.is_finite() && visible(*z))) {
+                chart
+                    .draw_series(LineSeries::new(
+                        path.iter().map(|&(x, y, z)| (x, z, y)),
+                        &BLACK,
+                    ))
+                    .unwrap();
+            }
+        }
+    }
+
+    fn plot_cartesian(
+        &self,
+        root: &DrawingArea<CanvasBackend, Shift>,
+        analysis: &Analysis,
+        context: &Context,
+    ) -> PlotArea {
+        let grid =
+            itertools_num::linspace(self.input.domain.0, self.input.domain.1, self.input.quality);
+
+        let mut curves: Vec<Vec<(f64, f64)>> = vec![];
+        let mut derivative_curves = vec![];
+        let mut antiderivatives = vec![];
+        let mut areas = vec![];
+        let mut taylor_polynomials = vec![];
+        let mut taylor_bands = vec![];
+        let mut series = vec![];
+        let mut fits = vec![];
+        let mut histograms = vec![];
+        let mut ecdfs = vec![];
+        let mut residuals = vec![];
+        let mut markers = vec![];
+        let mut implicit_curves = vec![];
+        let mut regions = vec![];
+        let mut functions_of_y = vec![];
+        let mut slope_fields = vec![];
+        let mut overall_min = f64::INFINITY;
+        let mut overall_max = f64::NEG_INFINITY;
+
+        let dependencies = &analysis.dependencies;
+
+        let domain = self.input.domain;
+        let extend_range = |points: &[(f64, f64)], min: &mut f64, max: &mut f64| {
+            let visible_values = points
+                .iter()
+                .filter(|(x, y)| (domain.0..=domain.1).contains(x) && y.is_finite())
+                .map(|(_, y)| y);
+            if let Some((low, high)) = visible_values.minmax().into_option() {
+                *min = low.min(*min);
+                *max = high.max(*max);
+            }
+        };
+
+        for (index, function_input) in self.input.functions.iter().enumerate() {
+            if let Some(error) = dependencies.errors.get(&index) {
+                log::error!("Function {} can not be plotted. {}", index, error);
+                continue;
+            }
+            if function_input.show() {
+                log::trace!("Computing values for function {:?}", function_input);
+                let kind = function_input.kind();
+                let order = function_input.derivatives;
+                if order > 0 && kind.is_callable() {
+                    let symbolic = function_input.derivative_expressions(order);
+                    let f = |x: f64| kind.eval(context, x);
+                    let h = (domain.1 - domain.0) * 1e-3;
+                    for k in 1..=order {
+                        let points: Vec<(f64, f64)> = match &symbolic {
+                            Some(derivatives) => grid
+                                .clone()
+                                .map(|x| (x, derivatives[k - 1].eval(context, &[("x", x)])))
+                                .collect(),
+                            None => grid.clone().map(|x| (x, derivative(&f, x, k, h))).collect(),
+                        };
+                        extend_range(&points, &mut overall_min, &mut overall_max);
+                        derivative_curves.push((points, k));
+                    }
+                }
+                let integral = function_input.integral;
+                if kind.is_callable() && integral.antiderivative {
+                    let f = |x: f64| kind.eval(context, x);
+                    let xs: Vec<f64> = grid.clone().collect();
+                    let values =
+                        cumulative_integral(&f, integral.lower_bound, &xs, integral.quadrature);
+                    let points: Vec<(f64, f64)> = xs.into_iter().zip(values).collect();
+                    extend_range(&points, &mut overall_min, &mut overall_max);
+                    antiderivatives.push(points);
+                }
+                if kind.is_callable() && integral.area {
+                    let (start, end) = integral.bounds;
+                    let mut outline = vec![(start, 0.)];
+                    outline.extend(
+                        itertools_num::linspace(start, end, self.input.quality)
+                            .map(|x| (x, kind.eval(context, x)))
+                            .filter(|(_, y)| y.is_finite()),
+                    );
+                    outline.push((end, 0.));
+                    areas.push(outline);
+                }
+                let settings = function_input.taylor;
+                if let Some(coefficients) = Some(settings)
+                    .filter(|settings| settings.enabled)
+                    .and_then(|settings| settings.coefficients(function_input, context))
+                {
+                    let points: Vec<(f64, f64)> = grid
+                        .clone()
+                        .map(|x| (x, taylor::polynomial(&coefficients, settings.center, x)))
+                        .collect();
+                    // Intervals of the grid where the error stays below the tolerance
+                    let close: Vec<bool> = points
+                        .iter()
+                        .map(|(x, y)| (kind.eval(context, *x) - y).abs() < settings.tolerance)
+                        .collect();
+                    for (i, pair) in points.windows(2).enumerate() {
+                        if close[i] && close[i + 1] {
+                            taylor_bands.push((pair[0].0, pair[1].0));
+                        }
+                    }
+                    // Not used for the range, since polynomials soon leave the plot
+                    taylor_polynomials.push(points);
+                }
+                let order = function_input.fourier_series;
+                if order > 0 && matches!(kind, FnInputKind::Analytical { .. }) {
+                    let f = |x: f64| kind.eval(context, x);
+                    let coefficients = fourier_coefficients(&f, domain, order);
+                    let points: Vec<(f64, f64)> = grid
+                        .clone()
+                        .map(|x| (x, fourier_series(&coefficients, domain, x)))
+                        .collect();
+                    extend_range(&points, &mut overall_min, &mut overall_max);
+                    series.push(points);
+                }
+                if let Some(result) = &analysis.fits[index] {
+                    let f = |x: f64| result.expression.eval(context, &[("x", x)]);
+                    let points: Vec<(f64, f64)> = grid.clone().map(|x| (x, f(x))).collect();
+                    extend_range(&points, &mut overall_min, &mut overall_max);
+                    fits.push(points);
+                    if let FnInputKind::Points {
+                        spline,
+                        interpolation,
+                    } = kind
+                    {
+                        residuals.extend(
+                            points::data(spline, *interpolation)
+                                .into_iter()
+                                .map(|(x, y)| vec![(x, y), (x, f(x))]),
+                        );
+                    }
+                }
+                let points: Vec<(f64, f64)> = match function_input.kind() {
+                    kind @ FnInputKind::Analytical { .. } => {
+                        grid.clone().map(|x| (x, kind.eval(context, x))).collect()
+                    }
+                    kind @ FnInputKind::Points {
+                        spline,
+                        interpolation,
+                    } => {
+                        let style = &function_input.point_style;
+                        if style.mode.has_markers() {
+                            let data = points::data(spline, *interpolation);
+                            extend_range(&data, &mut overall_min, &mut overall_max);
+                            markers.push((data, style));
+                        }
+                        if !style.mode.has_line() {
+                            continue;
+                        }
+                        grid.clone().map(|x| (x, kind.eval(context, x))).collect()
+                    }
+                    FnInputKind::Piecewise { branches } => {
+                        piecewise::sample(branches, context, grid.clone())
+                    }
+                    FnInputKind::Parametric { x, y, range } => range
+                        .grid(self.input.quality)
+                        .map(|t| {
+                            let bindings = [("t", t)];
+                            (x.eval(context, &bindings), y.eval(context, &bindings))
+                        })
+                        .collect(),
+                    FnInputKind::Polar { radius, range } => range
+                        .grid(self.input.quality)
+                        .map(|theta| {
+                            let r = radius.eval(context, &[("theta", theta)]);
+                            (r * theta.cos(), r * theta.sin())
+                        })
+                        .collect(),
+                    FnInputKind::Implicit { expression } => {
+                        // Computed once the vertical range is known
+                        implicit_curves.push(expression);
+                        continue;
+                    }
+                    FnInputKind::FunctionOfY { expression } => {
+                        functions_of_y.push(expression);
+                        continue;
+                    }
+                    FnInputKind::Inequality { condition } => {
+                        regions.push(condition);
+                        continue;
+                    }
+                    FnInputKind::Sample { values } => {
+                        let distribution = function_input.distribution;
+                        if distribution.histogram {
+                            let bins = histogram(values, distribution.bins(values));
+                            let corners: Vec<(f64, f64)> = bins
+                                .iter()
+                                .flat_map(|(start, end, height)| {
+                                    vec![(*start, 0.), (*start, *height), (*end, *height)]
+                                })
+                                .collect();
+                            extend_range(&corners, &mut overall_min, &mut overall_max);
+                            histograms.push(bins);
+                        }
+                        if distribution.ecdf {
+                            // Flat beyond the data, up to the edges of the domain
+                            let mut steps = vec![(domain.0.min(values[0]), 0.)];
+                            steps.extend(ecdf(values));
+                            steps.push((domain.1.max(values[values.len() - 1]), 1.));
+                            extend_range(&steps, &mut overall_min, &mut overall_max);
+                            ecdfs.push(steps);
+                        }
+                        if !distribution.kde {
+                            continue;
+                        }
+                        let bandwidth = silverman_bandwidth(values) * distribution.bandwidth_factor;
+                        grid.clone()
+                            .map(|x| (x, kde(values, bandwidth, x)))
+                            .collect()
+                    }
+                    FnInputKind::Surface { .. } | FnInputKind::Parametric3d { .. } => {
+                        log::trace!("Curves in space are only drawn in 3D mode.");
+                        continue;
+                    }
+                    FnInputKind::SlopeField {
+                        expression,
+                        initial_conditions,
+                    } => {
+                        let f = |x: f64, y: f64| expression.eval(context, &[("x", x), ("y", y)]);
+                        let step = (domain.1 - domain.0) / self.input.quality as f64;
+                        for &initial in initial_conditions {
+                            let points = solve(f, initial, domain, step);
+                            extend_range(&points, &mut overall_min, &mut overall_max);
+                            curves.push(points);
+                        }
+                        slope_fields.push(expression);
+                        continue;
+                    }
+                    FnInputKind::InitialValueProblem {
+                        expression,
+                        x0,
+                        initial_values,
+                    } => {
+                        let key = self.input.solution_key(function_input);
+                        let step = key.step;
+                        function_input.solution(key, || {
+                            initial_value_problem::solve(
+                                expression,
+                                context,
+                                (*x0, initial_values),
+                                domain,
+                                step,
+                                function_input.solver,
+                            )
+                        })
+                    }
+                };
+                extend_range(&points, &mut overall_min, &mut overall_max);
+                curves.push(points);
+            }
+        }
+
+        let (bottom, top) = if !self.input.auto_range {
+            self.input.range
+        } else if curves.is_empty()
+            && markers.is_empty()
+            && derivative_curves.is_empty()
+            && antiderivatives.is_empty()
+            && histograms.is_empty()
+            && ecdfs.is_empty()
+        {
+            if implicit_curves.is_empty() && regions.is_empty() && slope_fields.is_empty() {
+                log::trace!("There is no function to plot.");
+                (-1., 1.)
+            } else {
+                log::trace!(
+                    "There are only implicit curves, regions and slope fields, using the domain as range."
+                );
+                self.input.domain
+            }
+        } else {
+            log::trace!(
+                "Num max/min values of the plot: ({}, {})",
+                overall_max,
+                overall_min
+            );
+
+            if !overall_min.is_finite() {
+                log::error!("min value is not real!");
+                overall_min = -1.;
+                log::warn!("min value changed to {}", overall_min);
+            }
+            if !overall_max.is_finite() {
+                log::error!("max value is not real!");
+                overall_max = 1.;
+                log::warn!("max value changed to {}", overall_max);
+            }
+
+            let delta = overall_max - overall_min;
+            (overall_min - delta / 100., overall_max + delta / 100.)
+        };
+
+        let resolution = (self.input.quality, self.input.quality);
+        let segments: Vec<Segment> = implicit_curves
+            .into_iter()
+            .flat_map(|expression| {
+                let f = |x: f64, y: f64| expression.eval(context, &[("x", x), ("y", y)]);
+                Grid::new(f, self.input.domain, (bottom, top), resolution).contour(0.)
+            })
+            .collect();
+
+        for expression in functions_of_y {
+            let y_grid = itertools_num::linspace(bottom, top, self.input.quality);
+            curves.push(
+                y_grid
+                    .map(|y| (expression.eval(context, &[("y", y)]), y))
+                    .collect(),
+            );
+        }
+
+        let offset = (self.input.domain.1 - self.input.domain.0).min(top - bottom)
+            / self.input.quality as f64;
+        let mut region_cells = vec![];
+        let mut region_boundaries = vec![];
+        for condition in regions {
+            let inside = |x: f64, y: f64| {
+                condition.holds(|expression| expression.eval(context, &[("x", x), ("y", y)]))
+            };
+            region_cells.extend(cells(inside, self.input.domain, (bottom, top), resolution));
+            for comparison in condition.comparisons() {
+                let f = |x: f64, y: f64| {
+                    let bindings = [("x", x), ("y", y)];
+                    comparison.lhs.eval(context, &bindings)
+                        - comparison.rhs.eval(context, &bindings)
+                };
+                let segments =
+                    Grid::new(f, self.input.domain, (bottom, top), resolution).contour(0.);
+                let segments = boundary(segments, inside, offset / 2.);
+                for polyline in join(&segments, offset * 1e-6) {
+                    region_boundaries.push((polyline, comparison.relation.is_strict()));
+                }
+            }
+        }
+
+        let mut chart = self.chart(root, (bottom, top));
+        self.draw_mesh(&mut chart);
+        let plot_area = PlotArea::new(&chart, self.input.domain, (bottom, top));
+
+        // Segments have the same length in pixels whatever their slope.
+        let density = self.input.slope_field_density;
+        let (columns, rows) = &plot_area.pixels;
+        let x_scale = f64::from(columns.end - columns.start) / (domain.1 - domain.0);
+        let y_scale = f64::from(rows.end - rows.start) / (top - bottom);
+        let half_length = 0.35
+            * f64::from((columns.end - columns.start).min(rows.end - rows.start))
+            / density as f64;
+        let mut slope_segments = vec![];
+        for expression in slope_fields {
+            for i in 0..density {
+                let x = domain.0 + (i as f64 + 0.5) / density as f64 * (domain.1 - domain.0);
+                for j in 0..density {
+                    let y = bottom + (j as f64 + 0.5) / density as f64 * (top - bottom);
+                    let slope = expression.eval(context, &[("x", x), ("y", y)]);
+                    if !slope.is_finite() {
+                        continue;
+                    }
+                    let dx = half_length / x_scale.hypot(slope * y_scale);
+                    let dy = slope * dx;
+                    slope_segments.push(vec![(x - dx, y - dy), (x + dx, y + dy)]);
+                }
+            }
+        }
+        chart
+            .draw_series(
+                slope_segments
+                    .into_iter()
+                    .map(|segment| PathElement::new(segment, BLACK.mix(0.5))),
+            )
+            .unwrap();
+
+        chart
+            .draw_series(
+                region_cells
+                    .into_iter()
+                    .map(|(start, end)| Rectangle::new([start, end], BLACK.mix(0.2).filled())),
+            )
+            .unwrap();
+        for (polyline, strict) in region_boundaries {
+            if strict {
+                chart
+                    .draw_series(DashedLineSeries::new(polyline, 5, 5, BLACK.into()))
+                    .unwrap();
+            } else {
+                chart.draw_series(LineSeries::new(polyline, BLACK)).unwrap();
+            }
+        }
+
+        for points in curves {
+            // Non-finite values split the curve
+            for path in points.split(|(x, y)| !x.is_finite() || !y.is_finite()) {
+                chart
+                    .draw_series(LineSeries::new(path.iter().cloned(), &BLACK))
+                    .unwrap();
+            }
+        }
+        chart
+            .draw_series(taylor_bands.into_iter().map(|(start, end)| {
+                Rectangle::new([(start, bottom), (end, top)], GREEN.mix(0.15).filled())
+            }))
+            .unwrap();
+        for bins in histograms {
+            chart
+                .draw_series(bins.iter().map(|(start, end, height)| {
+                    Rectangle::new([(*start, 0.), (*end, *height)], BLUE.mix(0.3).filled())
+                }))
+                .unwrap();
+            chart
+                .draw_series(bins.iter().map(|(start, end, height)| {
+                    Rectangle::new([(*start, 0.), (*end, *height)], BLUE)
+                }))
+                .unwrap();
+        }
+        for steps in ecdfs {
+            chart.draw_series(LineSeries::new(steps, &BLACK)).unwrap();
+        }
+        chart
+            .draw_series(
+                residuals
+                    .into_iter()
+                    .map(|segment| PathElement::new(segment, RED.mix(0.5))),
+            )
+            .unwrap();
+        for points in fits {
+            for path in points.split(|(x, y)| !x.is_finite() || !y.is_finite()) {
+                chart
+                    .draw_series(LineSeries::new(path.iter().cloned(), &RED))
+                    .unwrap();
+            }
+        }
+        for points in series {
+            for path in points.split(|(x, y)| !x.is_finite() || !y.is_finite()) {
+                chart
+                    .draw_series(LineSeries::new(path.iter().cloned(), &MAGENTA))
+                    .unwrap();
+            }
+        }
+        for points in taylor_polynomials {
+            for path in points.split(|(x, y)| !x.is_finite() || !y.is_finite()) {
+                chart
+                    .draw_series(LineSeries::new(path.iter().cloned(), &GREEN))
+                    .unwrap();
+            }
+        }
+        chart
+            .draw_series(
+                areas
+                    .into_iter()
+                    .map(|outline| Polygon::new(outline, BLUE.mix(0.2).filled())),
+            )
+            .unwrap();
+        for points in antiderivatives {
+            for path in points.split(|(x, y)| !x.is_finite() || !y.is_finite()) {
+                chart
+                    .draw_series(DashedLineSeries::new(
+                        path.iter().cloned(),
+                        5,
+                        5,
+                        BLACK.into(),
+                    ))
+                    .unwrap();
+            }
+        }
+        for (points, order) in derivative_curves {
+            let color = Palette99::pick(order);
+            for path in points.split(|(x, y)| !x.is_finite() || !y.is_finite()) {
+                chart
+                    .draw_series(LineSeries::new(path.iter().cloned(), &color))
+                    .unwrap();
+            }
+        }
+        for (points, style) in markers {
+            let size = style.size as i32;
+            let points = points
+                .into_iter()
+                .filter(|(x, y)| x.is_finite() && y.is_finite());
+            match style.shape {
+                MarkerShape::Circle => chart
+                    .draw_series(points.map(|point| Circle::new(point, size, BLACK.filled())))
+                    .unwrap(),
+                MarkerShape::Cross => chart
+                    .draw_series(points.map(|point| Cross::new(point, size, BLACK)))
+                    .unwrap(),
+                MarkerShape::Triangle => chart
+                    .draw_series(
+                        points.map(|point| TriangleMarker::new(point, size, BLACK.filled())),
+                    )
+                    .unwrap(),
+                MarkerShape::Square => chart
+                    .draw_series(points.map(|point| {
+                        EmptyElement::at(point)
+                            + Rectangle::new([(-size, -size), (size, size)], BLACK.filled())
+                    }))
+                    .unwrap(),
+            };
+        }
+        chart
+            .draw_series(
+                segments
+                    .into_iter()
+                    .map(|(start, end)| PathElement::new(vec![start, end], BLACK)),
+            )
+            .unwrap();
+        chart
+            .draw_series(
+                analysis
+                    .key_points
+                    .iter()
+                    .filter(|key_point| (bottom..=top).contains(&key_point.point.1))
+                    .map(|key_point| {
+                        EmptyElement::at(key_point.point)
+                            + Circle::new((0, 0), 4, RED.filled())
+                            + Text::new(key_point.kind.name(), (6, -14), ("Arial", 12))
+                    }),
+            )
+            .unwrap();
+        if let Some(linearization) = &analysis.linearization {
+            self.draw_linearization(&mut chart, linearization, (bottom, top));
+        }
+        plot_area
+    }
+
+    /// Tangent line at the point of `linearization`, with the normal line and the
+    /// osculating circle if they are enabled.
+    fn draw_linearization(
+        &self,
+        chart: &mut Chart,
+        linearization: &Linearization,
+        (bottom, top): (f64, f64),
+    ) {
+        let domain = self.input.domain;
+        let (x0, y0) = linearization.point;
+        let tangent = vec![
+            (domain.0, linearization.tangent(domain.0)),
+            (domain.1, linearization.tangent(domain.1)),
+        ];
+        chart.draw_series(LineSeries::new(tangent, &BLUE)).unwrap();
+        if self.input.tangent.normal {
+            let normal = if linearization.slope == 0. {
+                vec![(x0, bottom), (x0, top)]
+            } else {
+                let normal = |x: f64| y0 - (x - x0) / linearization.slope;
+                vec![(domain.0, normal(domain.0)), (domain.1, normal(domain.1))]
+            };
+            chart
+                .draw_series(DashedLineSeries::new(normal, 5, 5, BLUE.into()))
+                .unwrap();
+        }
+        if let Some((center, radius)) = linearization
+            .osculating_circle()
+            .filter(|_| self.input.tangent.osculating_circle)
+        {
+            let circle = itertools_num::linspace(0., 2. * core::f64::consts::PI, 200)
+                .map(|t| (center.0 + radius * t.cos(), center.1 + radius * t.sin()));
+            chart
+                .draw_series(LineSeries::new(circle, BLUE.mix(0.6)))
+                .unwrap();
+        }
+        chart
+            .draw_series(std::iter::once(Circle::new(
+                linearization.point,
+                4,
+                BLUE.filled(),
+            )))
+            .unwrap();
+    }
+}
+
+/// Puts the image of the given `width`, made of RGBA `pixels`, on the canvas with
+/// its top left corner at `(x, y)`.
+fn put_image(canvas: &HtmlCanvasElement, pixels: &[u8], width: u32, (x, y): (i32, i32)) {
+    let context = canvas
+        .get_context("2d")
+        .ok()
+        .flatten()
+        .and_then(|context| context.dyn_into::<CanvasRenderingContext2d>().ok());
+    let image = ImageData::new_with_u8_clamped_array(Clamped(pixels), width);
+    match (context, image) {
+        (Some(context), Ok(image)) => {
+            if let Err(e) = context.put_image_data(&image, x.into(), y.into()) {
+                log::error!("Failed to draw the image: {:?}", e);
+            }
+        }
+        _ => log::error!("Failed to draw the image"),
+    }
+}
+
+/// Color of `value` in `colormap`, whose colors span the range `(bottom, top)`.
+fn colormap_color(colormap: Colormap, value: f64, (bottom, top): (f64, f64)) -> RGBColor {
+    match colormap {
+        Colormap::Viridis => ViridisRGB.get_color_normalized(value, bottom, top),
+        Colormap::Grayscale => BlackWhite.get_color_normalized(value, bottom, top),
+        Colormap::Bone => Bone.get_color_normalized(value, bottom, top),
+        Colormap::Copper => Copper.get_color_normalized(value, bottom, top),
+        Colormap::Volcano => {
+            let (r, g, b) = VulcanoHSL.get_color_normalized(value, bottom, top).rgb();
+            RGBColor(r, g, b)
+        }
     }
 }
 
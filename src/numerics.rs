@@ -0,0 +1,22 @@
+mod calculus;
+mod contour;
+mod domain_coloring;
+mod fitting;
+mod fourier;
+mod ode;
+mod phase_plane;
+mod region;
+mod roots;
+mod statistics;
+pub use calculus::{cumulative_integral, derivative, integrate, Quadrature};
+pub use contour::{join, Grid, Segment};
+pub use domain_coloring::color;
+pub use fitting::{levenberg_marquardt, linear_least_squares, polynomial_fit, r_squared};
+pub use fourier::{fourier_coefficients, fourier_series, spectrum};
+pub use ode::{solve, solve_system, Method};
+pub use phase_plane::fixed_points;
+pub use region::{boundary, cells};
+pub use roots::{extrema, zeros, Extremum};
+pub use statistics::{
+    ecdf, freedman_diaconis, histogram, kde, silverman_bandwidth, sturges, MAX_BINS,
+};
@@ -0,0 +1,275 @@
+use serde::{Deserialize, Serialize};
+
+/// Rule approximating integrals from values of the integrand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Quadrature {
+    Trapezoid,
+    Simpson,
+    /// Adaptive Gauss-Kronrod rule with 7 and 15 points.
+    #[default]
+    GaussKronrod,
+}
+
+impl Quadrature {
+    pub const ALL: [Quadrature; 3] = [
+        Quadrature::Trapezoid,
+        Quadrature::Simpson,
+        Quadrature::GaussKronrod,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Quadrature::Trapezoid => "trapezoid",
+            Quadrature::Simpson => "Simpson",
+            Quadrature::GaussKronrod => "Gauss-Kronrod",
+        }
+    }
+}
+
+/// Nodes of the Kronrod rule on `[-1, 1]`, from the outermost to the center,
+/// where those of odd index are the nodes of the Gauss rule.
+const KRONROD_NODES: [f64; 8] = [
+    0.991_455_371_120_812_6,
+    0.949_107_912_342_758_5,
+    0.864_864_423_359_769_1,
+    0.741_531_185_599_394_4,
+    0.586_087_235_467_691_1,
+    0.405_845_151_377_397_2,
+    0.207_784_955_007_898_5,
+    0.,
+];
+const KRONROD_WEIGHTS: [f64; 8] = [
+    0.022_935_322_010_529_22,
+    0.063_092_092_629_978_55,
+    0.104_790_010_322_250_18,
+    0.140_653_259_715_525_92,
+    0.169_004_726_639_267_9,
+    0.190_350_578_064_785_4,
+    0.204_432_940_075_298_9,
+    0.209_482_141_084_727_83,
+];
+const GAUSS_WEIGHTS: [f64; 4] = [
+    0.129_484_966_168_869_7,
+    0.279_705_391_489_276_7,
+    0.381_830_050_505_118_9,
+    0.417_959_183_673_469_4,
+];
+
+/// Largest number of subintervals of the adaptive rule.
+const MAX_SUBINTERVALS: usize = 1000;
+
+/// Integral of `f` over `(a, b)` and an estimate of its error.
+///
+/// Trapezoids and Simpson's rule use `panels` subintervals, and the error is estimated
+/// by comparing with half as many. The Gauss-Kronrod rule bisects the subintervals
+/// with the largest error until the error is below `tolerance`, relative to the integral
+/// of `|f|`, so that integrals that vanish by cancellation do not need more subintervals.
+pub fn integrate<F>(
+    f: &F,
+    (a, b): (f64, f64),
+    quadrature: Quadrature,
+    panels: usize,
+    tolerance: f64,
+) -> (f64, f64)
+where
+    F: Fn(f64) -> f64,
+{
+    if a == b {
+        return (0., 0.);
+    }
+    match quadrature {
+        Quadrature::Trapezoid => {
+            let panels = panels.max(2) / 2 * 2;
+            let fine = trapezoid(f, (a, b), panels);
+            let coarse = trapezoid(f, (a, b), panels / 2);
+            (fine, (fine - coarse).abs() / 3.)
+        }
+        Quadrature::Simpson => {
+            let panels = panels.max(4) / 4 * 4;
+            let fine = simpson(f, (a, b), panels);
+            let coarse = simpson(f, (a, b), panels / 2);
+            (fine, (fine - coarse).abs() / 15.)
+        }
+        Quadrature::GaussKronrod => {
+            let mut intervals = vec![((a, b), gauss_kronrod(f, (a, b)))];
+            loop {
+                let (value, error, magnitude) = intervals.iter().fold(
+                    (0., 0., 0.),
+                    |(value, error, magnitude), (_, (v, e, m))| {
+                        (value + v, error + e, magnitude + m)
+                    },
+                );
+                // A NaN error can not be improved either
+                if error <= tolerance * magnitude
+                    || error.is_nan()
+                    || intervals.len() >= MAX_SUBINTERVALS
+                {
+                    return (value, error);
+                }
+                let worst = (0..intervals.len())
+                    .max_by(|&i, &j| {
+                        let (e_i, e_j) = ((intervals[i].1).1, (intervals[j].1).1);
+                        e_i.partial_cmp(&e_j).unwrap_or(core::cmp::Ordering::Equal)
+                    })
+                    .unwrap_or(0);
+                let ((start, end), _) = intervals.swap_remove(worst);
+                let middle = (start + end) / 2.;
+                intervals.push(((start, middle), gauss_kronrod(f, (start, middle))));
+                intervals.push(((middle, end), gauss_kronrod(f, (middle, end))));
+            }
+        }
+    }
+}
+
+/// Values of `x ↦ ∫_a^x f` at each of `xs`, which are sorted.
+///
+/// The integral is accumulated outward from `a` in both directions, over the
+/// intervals between consecutive points, so that each value only depends on `f`
+/// between `a` and its point, and a singularity only spoils the values beyond it.
+pub fn cumulative_integral<F>(f: &F, a: f64, xs: &[f64], quadrature: Quadrature) -> Vec<f64>
+where
+    F: Fn(f64) -> f64,
+{
+    let split = xs.partition_point(|x| *x < a);
+    let mut values = vec![0.; xs.len()];
+    let right = (split..xs.len()).collect::<Vec<usize>>();
+    let left = (0..split).rev().collect::<Vec<usize>>();
+    for indices in [right, left].iter() {
+        let (mut total, mut previous) = (0., a);
+        for (step, &index) in indices.iter().enumerate() {
+            // The first interval, from `a`, may be much longer than the others.
+            let panels = if step == 0 { xs.len() } else { 4 };
+            total += integrate(f, (previous, xs[index]), quadrature, panels, 1e-10).0;
+            values[index] = total;
+            previous = xs[index];
+        }
+    }
+    values
+}
+
+fn trapezoid<F>(f: &F, (a, b): (f64, f64), panels: usize) -> f64
+where
+    F: Fn(f64) -> f64,
+{
+    let h = (b - a) / panels as f64;
+    let inner: f64 = (1..panels).map(|i| f(a + i as f64 * h)).sum();
+    h * ((f(a) + f(b)) / 2. + inner)
+}
+
+/// Simpson's rule, where `panels` is even.
+fn simpson<F>(f: &F, (a, b): (f64, f64), panels: usize) -> f64
+where
+    F: Fn(f64) -> f64,
+{
+    let h = (b - a) / panels as f64;
+    let inner: f64 = (1..panels)
+        .map(|i| f(a + i as f64 * h) * if i % 2 == 1 { 4. } else { 2. })
+        .sum();
+    h / 3. * (f(a) + f(b) + inner)
+}
+
+/// Integral by the Kronrod rule, the difference with the Gauss rule and the
+/// integral of `|f|` by the Kronrod rule.
+fn gauss_kronrod<F>(f: &F, (a, b): (f64, f64)) -> (f64, f64, f64)
+where
+    F: Fn(f64) -> f64,
+{
+    let (center, radius) = ((a + b) / 2., (b - a) / 2.);
+    let mut kronrod = 0.;
+    let mut gauss = 0.;
+    let mut magnitude = 0.;
+    for (i, (node, weight)) in KRONROD_NODES.iter().zip(&KRONROD_WEIGHTS).enumerate() {
+        let (values, absolute) = if *node == 0. {
+            let value = f(center);
+            (value, value.abs())
+        } else {
+            let (left, right) = (f(center - radius * node), f(center + radius * node));
+            (left + right, left.abs() + right.abs())
+        };
+        kronrod += weight * values;
+        magnitude += weight * absolute;
+        if i % 2 == 1 {
+            gauss += GAUSS_WEIGHTS[i / 2] * values;
+        }
+    }
+    (
+        radius * kronrod,
+        (radius * (kronrod - gauss)).abs(),
+        (radius * magnitude).abs(),
+    )
+}
+
+/// Derivative of order `order` of `f` at `x`, by central differences with step `h`.
+///
+/// Each order doubles the number of evaluations of `f`.
+pub fn derivative<F>(f: &F, x: f64, order: usize, h: f64) -> f64
+where
+    F: Fn(f64) -> f64,
+{
+    match order {
+        0 => f(x),
+        _ => (derivative(f, x + h, order - 1, h) - derivative(f, x - h, order - 1, h)) / (2. * h),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::f64::consts::PI;
+
+    #[test]
+    fn central_differences() {
+        let f = |x: f64| x.sin();
+        assert!((derivative(&f, 1., 1, 1e-4) - 1f64.cos()).abs() < 1e-8);
+        assert!((derivative(&f, 1., 2, 1e-3) + 1f64.sin()).abs() < 1e-5);
+        assert_eq!(derivative(&f, 1., 0, 1e-3), 1f64.sin());
+    }
+
+    #[test]
+    fn quadratures() {
+        let f = |x: f64| x.exp();
+        let exact = 1f64.exp() - 1.;
+        for &(quadrature, tolerance) in &[
+            (Quadrature::Trapezoid, 1e-4),
+            (Quadrature::Simpson, 1e-10),
+            (Quadrature::GaussKronrod, 1e-13),
+        ] {
+            let (value, error) = integrate(&f, (0., 1.), quadrature, 100, 1e-12);
+            assert!((value - exact).abs() < tolerance, "{}", quadrature.name());
+            assert!(error < tolerance && error >= 0., "{}", quadrature.name());
+        }
+        // Singular at zero, but integrable.
+        let (value, _) = integrate(
+            &|x: f64| 1. / x.sqrt(),
+            (0., 1.),
+            Quadrature::GaussKronrod,
+            0,
+            1e-8,
+        );
+        assert!((value - 2.).abs() < 1e-6);
+
+        // Vanishing integrals do not bisect up to the limit of subintervals.
+        let evaluations = core::cell::Cell::new(0);
+        let f = |x: f64| {
+            evaluations.set(evaluations.get() + 1);
+            x.cos() * (20. * x).cos()
+        };
+        let (value, _) = integrate(&f, (0., 2. * PI), Quadrature::GaussKronrod, 0, 1e-10);
+        assert!(value.abs() < 1e-10);
+        assert!(evaluations.get() < 1000, "{}", evaluations.get());
+
+        let xs = [-1., 0., 1., 2.];
+        let values = cumulative_integral(&|x: f64| 2. * x, 0., &xs, Quadrature::Simpson);
+        for (x, value) in xs.iter().zip(values) {
+            assert!((value - x * x).abs() < 1e-12);
+        }
+        // ln is not defined for negative x, which does not spoil the positive ones.
+        let xs: Vec<f64> = itertools_num::linspace(-PI, PI, 100).collect();
+        let values = cumulative_integral(&|x: f64| x.ln(), 1., &xs, Quadrature::GaussKronrod);
+        for (x, value) in xs.iter().zip(values) {
+            if *x > 0. {
+                assert!((value - (x * x.ln() - x + 1.)).abs() < 1e-8, "{}", x);
+            }
+        }
+    }
+}
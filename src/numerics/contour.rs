@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+/// A straight line between two points of the plane.
+pub type Segment = ((f64, f64), (f64, f64));
+
+/// Values of a function of two variables sampled on a rectangular grid.
+#[derive(Debug, Clone)]
+pub struct Grid {
+    pub x: Vec<f64>,
+    pub y: Vec<f64>,
+    /// `values[j][i]` is the value at `(x[i], y[j])`.
+    pub values: Vec<Vec<f64>>,
+}
+
+impl Grid {
+    /// Samples `f` over `x_range` and `y_range`, with `resolution` points in each direction.
+    pub fn new<F>(
+        f: F,
+        x_range: (f64, f64),
+        y_range: (f64, f64),
+        resolution: (usize, usize),
+    ) -> Self
+    where
+        F: Fn(f64, f64) -> f64,
+    {
+        let x: Vec<f64> = itertools_num::linspace(x_range.0, x_range.1, resolution.0).collect();
+        let y: Vec<f64> = itertools_num::linspace(y_range.0, y_range.1, resolution.1).collect();
+        let values = y
+            .iter()
+            .map(|&y| x.iter().map(|&x| f(x, y)).collect())
+            .collect();
+        Grid { x, y, values }
+    }
+
+    /// Segments approximating the set where the sampled function equals `level`,
+    /// computed by marching squares.
+    ///
+    /// Cells with a non-finite value at some corner are skipped.
+    /// Saddle cells are resolved with the average of the corners.
+    pub fn contour(&self, level: f64) -> Vec<Segment> {
+        let mut segments = Vec::new();
+        for j in 0..self.y.len().saturating_sub(1) {
+            for i in 0..self.x.len().saturating_sub(1) {
+                // Counterclockwise, starting from the bottom left corner.
+                let corners = [
+                    (self.x[i], self.y[j], self.values[j][i]),
+                    (self.x[i + 1], self.y[j], self.values[j][i + 1]),
+                    (self.x[i + 1], self.y[j + 1], self.values[j + 1][i + 1]),
+                    (self.x[i], self.y[j + 1], self.values[j + 1][i]),
+                ];
+                if corners.iter().any(|(_, _, v)| !v.is_finite()) {
+                    continue;
+                }
+                let above = corners.map(|(_, _, v)| v > level);
+                // Edge `k` goes from corner `k` to corner `k + 1`.
+                let crossing = |k: usize| {
+                    let (x0, y0, v0) = corners[k];
+                    let (x1, y1, v1) = corners[(k + 1) % 4];
+                    let s = (level - v0) / (v1 - v0);
+                    (x0 + s * (x1 - x0), y0 + s * (y1 - y0))
+                };
+                let crossed: Vec<usize> =
+                    (0..4).filter(|&k| above[k] != above[(k + 1) % 4]).collect();
+                match crossed.len() {
+                    2 => segments.push((crossing(crossed[0]), crossing(crossed[1]))),
+                    4 => {
+                        // Cut off the corners that are not connected through the center.
+                        let center = corners.iter().map(|(_, _, v)| v).sum::<f64>() / 4.;
+                        for k in (0..4).filter(|&k| above[k] != (center > level)) {
+                            segments.push((crossing((k + 3) % 4), crossing(k)));
+                        }
+                    }
+                    _ => (),
+                }
+            }
+        }
+        segments
+    }
+}
+
+/// Chains segments sharing endpoints into polylines.
+///
+/// Endpoints closer than `tolerance` in both coordinates are considered equal.
+pub fn join(segments: &[Segment], tolerance: f64) -> Vec<Vec<(f64, f64)>> {
+    let key = |(x, y): (f64, f64)| {
+        (
+            (x / tolerance).round() as i64,
+            (y / tolerance).round() as i64,
+        )
+    };
+    let mut ends: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+    for (index, (start, end)) in segments.iter().enumerate() {
+        ends.entry(key(*start)).or_default().push(index);
+        ends.entry(key(*end)).or_default().push(index);
+    }
+    // Degenerate segments, where the curve only touches a cell corner, are skipped.
+    let mut used: Vec<bool> = segments
+        .iter()
+        .map(|(start, end)| key(*start) == key(*end))
+        .collect();
+    // Unused segment at `point`, oriented so that it starts there.
+    let next = |point: (f64, f64), used: &mut Vec<bool>| {
+        let index = *ends.get(&key(point))?.iter().find(|&&index| !used[index])?;
+        used[index] = true;
+        let (start, end) = segments[index];
+        Some(if key(start) == key(point) { end } else { start })
+    };
+
+    let mut polylines = Vec::new();
+    for index in 0..segments.len() {
+        if used[index] {
+            continue;
+        }
+        used[index] = true;
+        let (start, end) = segments[index];
+        let mut forward = vec![start, end];
+        while let Some(point) = next(*forward.last().unwrap(), &mut used) {
+            forward.push(point);
+        }
+        let mut backward = vec![];
+        let mut first = start;
+        while let Some(point) = next(first, &mut used) {
+            backward.push(point);
+            first = point;
+        }
+        backward.reverse();
+        backward.extend(forward);
+        polylines.push(backward);
+    }
+    polylines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn circle() {
+        let grid = Grid::new(|x, y| x * x + y * y, (-2., 2.), (-2., 2.), (41, 41));
+        let segments = grid.contour(1.);
+        assert!(!segments.is_empty());
+        for ((x0, y0), (x1, y1)) in &segments {
+            assert!(((x0 * x0 + y0 * y0).sqrt() - 1.).abs() < 0.01);
+            assert!(((x1 * x1 + y1 * y1).sqrt() - 1.).abs() < 0.01);
+        }
+        let polylines = join(&segments, 1e-9);
+        assert_eq!(polylines.len(), 1);
+        // The circle is closed.
+        let (first, last) = (polylines[0][0], polylines[0][polylines[0].len() - 1]);
+        assert!((first.0 - last.0).abs() < 1e-9 && (first.1 - last.1).abs() < 1e-9);
+    }
+}
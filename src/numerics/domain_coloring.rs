@@ -0,0 +1,34 @@
+use num_complex::Complex;
+
+/// Hue, saturation and lightness, each between zero and one, representing `w`:
+/// the hue gives its argument and the lightness its modulus, from black at zero
+/// to white at infinity.
+///
+/// With `contours`, the lightness is modulated so that lines appear where the
+/// modulus is a power of two. Values that are not numbers are gray.
+pub fn color(w: Complex<f64>, contours: bool) -> (f64, f64, f64) {
+    if w.re.is_nan() || w.im.is_nan() {
+        return (0., 0., 0.5);
+    }
+    let modulus = w.norm();
+    let hue = (w.arg() / core::f64::consts::TAU).rem_euclid(1.);
+    let mut lightness = 1. - 1. / (1. + modulus.powf(0.4));
+    if contours && modulus.is_finite() && modulus > 0. {
+        lightness *= 0.75 + 0.25 * modulus.log2().rem_euclid(1.);
+    }
+    (hue, 1., lightness)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extremes() {
+        assert_eq!(color(Complex::new(0., 0.), false).2, 0.);
+        assert_eq!(color(Complex::new(f64::INFINITY, 0.), false).2, 1.);
+        let (hue, _, lightness) = color(Complex::new(0., 1.), false);
+        assert_eq!((hue, lightness), (0.25, 0.5));
+        assert_eq!(color(Complex::new(-1., 0.), false).0, 0.5);
+    }
+}
@@ -0,0 +1,188 @@
+/// Coefficients `c` minimizing `|A c - ys|`, where `rows` are the rows of `A`,
+/// by solving the normal equations.
+pub fn linear_least_squares(rows: &[Vec<f64>], ys: &[f64]) -> Option<Vec<f64>> {
+    let columns = rows.first()?.len();
+    let mut normal = vec![vec![0.; columns]; columns];
+    let mut rhs = vec![0.; columns];
+    for (row, y) in rows.iter().zip(ys) {
+        for i in 0..columns {
+            rhs[i] += row[i] * y;
+            for j in 0..columns {
+                normal[i][j] += row[i] * row[j];
+            }
+        }
+    }
+    solve_linear(normal, rhs)
+}
+
+/// Coefficients, from the constant one up, of the polynomial of degree `degree`
+/// fitting `points` by least squares.
+pub fn polynomial_fit(points: &[(f64, f64)], degree: usize) -> Option<Vec<f64>> {
+    if points.len() <= degree {
+        return None;
+    }
+    let rows: Vec<Vec<f64>> = points
+        .iter()
+        .map(|(x, _)| (0..=degree).map(|k| x.powi(k as i32)).collect())
+        .collect();
+    let ys: Vec<f64> = points.iter().map(|(_, y)| *y).collect();
+    linear_least_squares(&rows, &ys)
+}
+
+/// Parameters of `model` fitting `points` by least squares, found by the
+/// Levenberg-Marquardt method from `initial`.
+pub fn levenberg_marquardt<F>(model: &F, points: &[(f64, f64)], initial: &[f64]) -> Option<Vec<f64>>
+where
+    F: Fn(&[f64], f64) -> f64,
+{
+    let cost = |parameters: &[f64]| -> f64 {
+        points
+            .iter()
+            .map(|(x, y)| (y - model(parameters, *x)).powi(2))
+            .sum()
+    };
+    let mut parameters = initial.to_vec();
+    let mut current = cost(&parameters);
+    let mut damping = 1e-3;
+    for _ in 0..200 {
+        // Jacobian of the model by forward differences
+        let values: Vec<f64> = points.iter().map(|(x, _)| model(&parameters, *x)).collect();
+        let jacobian: Vec<Vec<f64>> = points
+            .iter()
+            .zip(&values)
+            .map(|((x, _), value)| {
+                (0..parameters.len())
+                    .map(|j| {
+                        let mut moved = parameters.clone();
+                        let h = 1e-7 * (1. + parameters[j].abs());
+                        moved[j] += h;
+                        (model(&moved, *x) - value) / h
+                    })
+                    .collect()
+            })
+            .collect();
+        let n = parameters.len();
+        let mut normal = vec![vec![0.; n]; n];
+        let mut gradient = vec![0.; n];
+        for ((row, value), (_, y)) in jacobian.iter().zip(&values).zip(points) {
+            for i in 0..n {
+                gradient[i] += row[i] * (y - value);
+                for j in 0..n {
+                    normal[i][j] += row[i] * row[j];
+                }
+            }
+        }
+        let mut improved = false;
+        while damping < 1e12 {
+            let mut damped = normal.clone();
+            for (i, row) in damped.iter_mut().enumerate() {
+                row[i] += damping * normal[i][i].max(1e-12);
+            }
+            let step = match solve_linear(damped, gradient.clone()) {
+                Some(step) => step,
+                None => break,
+            };
+            let proposal: Vec<f64> = parameters.iter().zip(&step).map(|(p, s)| p + s).collect();
+            let proposed = cost(&proposal);
+            if proposed.is_finite() && proposed < current {
+                let converged = current - proposed <= 1e-12 * current;
+                parameters = proposal;
+                current = proposed;
+                damping = (damping / 10.).max(1e-12);
+                if converged {
+                    return Some(parameters);
+                }
+                improved = true;
+                break;
+            }
+            damping *= 10.;
+        }
+        if !improved {
+            break;
+        }
+    }
+    if parameters.iter().all(|p| p.is_finite()) {
+        Some(parameters)
+    } else {
+        None
+    }
+}
+
+/// Coefficient of determination of `f` on `points`.
+pub fn r_squared<F>(f: &F, points: &[(f64, f64)]) -> f64
+where
+    F: Fn(f64) -> f64,
+{
+    let mean = points.iter().map(|(_, y)| y).sum::<f64>() / points.len() as f64;
+    let residual: f64 = points.iter().map(|(x, y)| (y - f(*x)).powi(2)).sum();
+    let total: f64 = points.iter().map(|(_, y)| (y - mean).powi(2)).sum();
+    1. - residual / total
+}
+
+/// Solution of `matrix x = rhs` by Gaussian elimination with partial pivoting,
+/// unless the matrix is singular.
+fn solve_linear(mut matrix: Vec<Vec<f64>>, mut rhs: Vec<f64>) -> Option<Vec<f64>> {
+    let n = rhs.len();
+    for column in 0..n {
+        let pivot = (column..n).max_by(|&a, &b| {
+            matrix[a][column]
+                .abs()
+                .partial_cmp(&matrix[b][column].abs())
+                .unwrap_or(core::cmp::Ordering::Equal)
+        })?;
+        if !matrix[pivot][column].is_normal() {
+            return None;
+        }
+        matrix.swap(column, pivot);
+        rhs.swap(column, pivot);
+        let (upper, lower) = matrix.split_at_mut(column + 1);
+        let pivot_row = &upper[column];
+        for (offset, row) in lower.iter_mut().enumerate() {
+            let factor = row[column] / pivot_row[column];
+            for (value, pivot) in row[column..].iter_mut().zip(&pivot_row[column..]) {
+                *value -= factor * pivot;
+            }
+            rhs[column + 1 + offset] -= factor * rhs[column];
+        }
+    }
+    let mut solution = vec![0.; n];
+    for row in (0..n).rev() {
+        let known: f64 = (row + 1..n).map(|k| matrix[row][k] * solution[k]).sum();
+        solution[row] = (rhs[row] - known) / matrix[row][row];
+    }
+    Some(solution)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fits() {
+        let points: Vec<(f64, f64)> = (0..10)
+            .map(|i| {
+                let x = i as f64 / 2.;
+                (x, 1. - 2. * x + 0.5 * x * x)
+            })
+            .collect();
+        let coefficients = polynomial_fit(&points, 2).unwrap();
+        for (coefficient, expected) in coefficients.iter().zip(&[1., -2., 0.5]) {
+            assert!((coefficient - expected).abs() < 1e-9);
+        }
+        assert!(polynomial_fit(&points[..2], 2).is_none());
+        let f = |x: f64| 1. - 2. * x + 0.5 * x * x;
+        assert!((r_squared(&f, &points) - 1.).abs() < 1e-12);
+
+        let model = |p: &[f64], x: f64| p[0] * (-p[1] * x).exp() + p[2];
+        let points: Vec<(f64, f64)> = (0..20)
+            .map(|i| {
+                let x = i as f64 / 4.;
+                (x, model(&[2., 0.5, 1.], x))
+            })
+            .collect();
+        let parameters = levenberg_marquardt(&model, &points, &[1., 1., 1.]).unwrap();
+        for (parameter, expected) in parameters.iter().zip(&[2., 0.5, 1.]) {
+            assert!((parameter - expected).abs() < 1e-6, "{:?}", parameters);
+        }
+    }
+}
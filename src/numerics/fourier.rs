@@ -0,0 +1,107 @@
+use super::{integrate, Quadrature};
+use core::f64::consts::PI;
+use num_complex::Complex;
+
+/// Discrete Fourier transform `X_k = Σ_j x_j e^{-2πi jk/n}` of `samples`,
+/// computed directly, which is fast enough for the size of the grids.
+pub fn dft(samples: &[f64]) -> Vec<Complex<f64>> {
+    let n = samples.len();
+    // Powers of the root of unity, since jk only matters modulo n
+    let roots: Vec<Complex<f64>> = (0..n)
+        .map(|m| Complex::from_polar(1., -2. * PI * m as f64 / n as f64))
+        .collect();
+    (0..n)
+        .map(|k| {
+            samples
+                .iter()
+                .enumerate()
+                .map(|(j, sample)| roots[j * k % n] * sample)
+                .sum()
+        })
+        .collect()
+}
+
+/// One-sided spectrum of samples taken every `spacing`, as the frequency, the
+/// amplitude and the phase of each of the first `n / 2 + 1` terms of the transform.
+pub fn spectrum(samples: &[f64], spacing: f64) -> Vec<(f64, f64, f64)> {
+    let n = samples.len();
+    dft(samples)
+        .into_iter()
+        .take(n / 2 + 1)
+        .enumerate()
+        .map(|(k, term)| {
+            // Both halves of the transform contribute to the amplitude, but for the
+            // constant term and the Nyquist frequency.
+            let weight = if k == 0 || 2 * k == n { 1. } else { 2. };
+            let frequency = k as f64 / (n as f64 * spacing);
+            (frequency, weight * term.norm() / n as f64, term.arg())
+        })
+        .collect()
+}
+
+/// Coefficients `(a_k, b_k)`, for `k` up to `order`, of the Fourier series of `f`
+/// as a function of period `b - a`.
+pub fn fourier_coefficients<F>(f: &F, (a, b): (f64, f64), order: usize) -> Vec<(f64, f64)>
+where
+    F: Fn(f64) -> f64,
+{
+    let omega = 2. * PI / (b - a);
+    let coefficient = |g: &dyn Fn(f64) -> f64| {
+        let (value, _) = integrate(&g, (a, b), Quadrature::GaussKronrod, 1, 1e-10);
+        2. * value / (b - a)
+    };
+    (0..=order)
+        .map(|k| {
+            let k = k as f64;
+            (
+                coefficient(&|x| f(x) * (k * omega * (x - a)).cos()),
+                coefficient(&|x| f(x) * (k * omega * (x - a)).sin()),
+            )
+        })
+        .collect()
+}
+
+/// Value at `x` of the Fourier series with `coefficients` of period `b - a`.
+pub fn fourier_series(coefficients: &[(f64, f64)], (a, b): (f64, f64), x: f64) -> f64 {
+    let omega = 2. * PI / (b - a);
+    coefficients
+        .iter()
+        .enumerate()
+        .map(|(k, (cosine, sine))| {
+            if k == 0 {
+                cosine / 2.
+            } else {
+                let angle = k as f64 * omega * (x - a);
+                cosine * angle.cos() + sine * angle.sin()
+            }
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transforms() {
+        // 1 + 3 cos(2 t) sampled at 8 points over a period
+        let samples: Vec<f64> = (0..8)
+            .map(|j| 1. + 3. * (2. * PI * 2. * j as f64 / 8.).cos())
+            .collect();
+        let spectrum = spectrum(&samples, 1. / 8.);
+        assert_eq!(spectrum.len(), 5);
+        assert!((spectrum[0].1 - 1.).abs() < 1e-12);
+        assert!((spectrum[2].0 - 2.).abs() < 1e-12 && (spectrum[2].1 - 3.).abs() < 1e-12);
+        assert!(spectrum[1].1.abs() < 1e-12 && spectrum[3].1.abs() < 1e-12);
+
+        // Square wave, whose series only has odd sines, with b_k = 4 / (pi k)
+        let square = |x: f64| if x < 0. { -1. } else { 1. };
+        let coefficients = fourier_coefficients(&square, (-PI, PI), 3);
+        assert!(coefficients[0].0.abs() < 1e-8);
+        assert!((coefficients[1].1 + 4. / PI).abs() < 1e-8);
+        assert!(coefficients[2].1.abs() < 1e-8);
+        let f = |x: f64| x.cos() - 2. * (3. * x).sin();
+        let coefficients = fourier_coefficients(&f, (0., 2. * PI), 4);
+        assert!((fourier_series(&coefficients, (0., 2. * PI), 1.) - f(1.)).abs() < 1e-8);
+    }
+}
@@ -0,0 +1,246 @@
+use serde::{Deserialize, Serialize};
+
+/// Numerical method integrating a differential equation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Method {
+    /// Explicit Euler method, of order one.
+    Euler,
+    /// Classical Runge-Kutta method, of order four.
+    RungeKutta4,
+    /// Dormand-Prince pair of orders four and five, adapting the step to the tolerance.
+    #[default]
+    RungeKutta45,
+}
+
+impl Method {
+    pub const ALL: [Method; 3] = [Method::Euler, Method::RungeKutta4, Method::RungeKutta45];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Method::Euler => "Euler",
+            Method::RungeKutta4 => "RK4",
+            Method::RungeKutta45 => "RK45",
+        }
+    }
+}
+
+/// Largest number of points computed in each direction, so that a step
+/// shrinking near a singularity can not hang the plot.
+const MAX_POINTS: usize = 100_000;
+
+/// Coefficients of the Runge-Kutta method of order four.
+const RK4: [&[f64]; 4] = [&[], &[0.5], &[0., 0.5], &[0., 0., 1.]];
+const RK4_WEIGHTS: [f64; 4] = [1. / 6., 1. / 3., 1. / 3., 1. / 6.];
+
+/// Coefficients of the Dormand-Prince method, whose last stage is
+/// the solution of order five.
+const DORMAND_PRINCE: [&[f64]; 7] = [
+    &[],
+    &[1. / 5.],
+    &[3. / 40., 9. / 40.],
+    &[44. / 45., -56. / 15., 32. / 9.],
+    &[
+        19372. / 6561.,
+        -25360. / 2187.,
+        64448. / 6561.,
+        -212. / 729.,
+    ],
+    &[
+        9017. / 3168.,
+        -355. / 33.,
+        46732. / 5247.,
+        49. / 176.,
+        -5103. / 18656.,
+    ],
+    &[
+        35. / 384.,
+        0.,
+        500. / 1113.,
+        125. / 192.,
+        -2187. / 6784.,
+        11. / 84.,
+    ],
+];
+/// Weights of the embedded solution of order four of the Dormand-Prince method.
+const DORMAND_PRINCE_WEIGHTS: [f64; 7] = [
+    5179. / 57600.,
+    0.,
+    7571. / 16695.,
+    393. / 640.,
+    -92097. / 339200.,
+    187. / 2100.,
+    1. / 40.,
+];
+
+/// Solution of the system `y' = f(x, y)` through `(x0, y0)` over `(start, end)`,
+/// computed in both directions from `x0`.
+///
+/// Fixed step methods use steps of length `step`, while the adaptive method keeps
+/// the error estimate of each step below `tolerance`, relative to the size of `y`,
+/// with steps no longer than `step`. The solution is cut where it stops being finite.
+pub fn solve_system<F>(
+    f: F,
+    (x0, y0): (f64, &[f64]),
+    (start, end): (f64, f64),
+    method: Method,
+    step: f64,
+    tolerance: f64,
+) -> Vec<(f64, Vec<f64>)>
+where
+    F: Fn(f64, &[f64]) -> Vec<f64>,
+{
+    let mut points = integrate(&f, (x0, y0), start, method, step, tolerance);
+    points.reverse();
+    points.pop();
+    points.extend(integrate(&f, (x0, y0), end, method, step, tolerance));
+    points
+}
+
+/// Solution of `y' = f(x, y)` through `initial` over `(start, end)`, computed by
+/// the classical Runge-Kutta method with steps of length `step`.
+pub fn solve<F>(f: F, initial: (f64, f64), range: (f64, f64), step: f64) -> Vec<(f64, f64)>
+where
+    F: Fn(f64, f64) -> f64,
+{
+    let f = |x: f64, y: &[f64]| vec![f(x, y[0])];
+    solve_system(
+        f,
+        (initial.0, &[initial.1]),
+        range,
+        Method::RungeKutta4,
+        step,
+        0.,
+    )
+    .into_iter()
+    .map(|(x, y)| (x, y[0]))
+    .collect()
+}
+
+/// Points from `(x0, y0)` to `end`, which may be on either side of `x0`.
+fn integrate<F>(
+    f: &F,
+    (x0, y0): (f64, &[f64]),
+    end: f64,
+    method: Method,
+    step: f64,
+    tolerance: f64,
+) -> Vec<(f64, Vec<f64>)>
+where
+    F: Fn(f64, &[f64]) -> Vec<f64>,
+{
+    let mut points = vec![(x0, y0.to_vec())];
+    if step.is_nan() || step <= 0. || !is_finite(y0) {
+        return points;
+    }
+    let direction = (end - x0).signum();
+    let (mut x, mut y) = (x0, y0.to_vec());
+    let mut length = step;
+    while direction * (end - x) > 0. && points.len() < MAX_POINTS {
+        let h = direction * length.min(direction * (end - x));
+        let next = match method {
+            Method::Euler => combination(&y, h, &[f(x, &y)], &[1.]),
+            Method::RungeKutta4 => {
+                let stages = stages(f, (x, &y), h, &RK4);
+                combination(&y, h, &stages, &RK4_WEIGHTS)
+            }
+            Method::RungeKutta45 => {
+                let stages = stages(f, (x, &y), h, &DORMAND_PRINCE);
+                let fifth = combination(&y, h, &stages[..6], DORMAND_PRINCE[6]);
+                let fourth = combination(&y, h, &stages, &DORMAND_PRINCE_WEIGHTS);
+                let error = fifth
+                    .iter()
+                    .zip(&fourth)
+                    .zip(&y)
+                    .map(|((a, b), y)| (a - b).abs() / (1. + y.abs()))
+                    .fold(0., f64::max);
+                let factor = if error > 0. {
+                    (0.9 * (tolerance / error).powf(0.2)).clamp(0.2, 5.)
+                } else {
+                    5.
+                };
+                if error > tolerance {
+                    length = h.abs() * factor;
+                    if length < step * 1e-9 || !error.is_finite() {
+                        break;
+                    }
+                    continue;
+                }
+                length = (h.abs() * factor).min(step);
+                fifth
+            }
+        };
+        x += h;
+        y = next;
+        if !is_finite(&y) {
+            break;
+        }
+        points.push((x, y.clone()));
+    }
+    points
+}
+
+/// Derivatives at the stages of the explicit Runge-Kutta method with the
+/// coefficients `tableau`, where the nodes are the sums of the rows.
+fn stages<F>(f: &F, (x, y): (f64, &[f64]), h: f64, tableau: &[&[f64]]) -> Vec<Vec<f64>>
+where
+    F: Fn(f64, &[f64]) -> Vec<f64>,
+{
+    let mut stages: Vec<Vec<f64>> = Vec::with_capacity(tableau.len());
+    for row in tableau {
+        let node: f64 = row.iter().sum();
+        let point = combination(y, h, &stages, row);
+        stages.push(f(x + node * h, &point));
+    }
+    stages
+}
+
+/// `y + h * (weights[0] * derivatives[0] + ...)`.
+fn combination(y: &[f64], h: f64, derivatives: &[Vec<f64>], weights: &[f64]) -> Vec<f64> {
+    let mut result = y.to_vec();
+    for (derivative, weight) in derivatives.iter().zip(weights) {
+        for (value, slope) in result.iter_mut().zip(derivative) {
+            *value += h * weight * slope;
+        }
+    }
+    result
+}
+
+fn is_finite(y: &[f64]) -> bool {
+    y.iter().all(|value| value.is_finite())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exponential() {
+        let points = solve(|_, y| y, (0., 1.), (-1., 1.), 0.01);
+        assert_eq!(points.len(), 201);
+        let (first, last) = (points[0], points[points.len() - 1]);
+        assert!((first.0 + 1.).abs() < 1e-12 && (first.1 - (-1f64).exp()).abs() < 1e-9);
+        assert!((last.0 - 1.).abs() < 1e-12 && (last.1 - 1f64.exp()).abs() < 1e-9);
+
+        // y' = y^2 from y(0) = 1 blows up at x = 1.
+        let points = solve(|_, y| y * y, (0., 1.), (0., 2.), 0.01);
+        assert!(points.iter().all(|(x, y)| *x < 1.1 && y.is_finite()));
+    }
+
+    #[test]
+    fn methods() {
+        // y'' = -y with y(0) = 0 and y'(0) = 1, whose solution is the sine.
+        let f = |_: f64, y: &[f64]| vec![y[1], -y[0]];
+        for &(method, error) in &[
+            (Method::Euler, 1e-2),
+            (Method::RungeKutta4, 1e-9),
+            (Method::RungeKutta45, 1e-5),
+        ] {
+            let points = solve_system(f, (0., &[0., 1.]), (-3., 3.), method, 0.01, 1e-8);
+            let (first, last) = (&points[0], &points[points.len() - 1]);
+            assert_eq!((first.0, last.0), (-3., 3.));
+            for (x, y) in &points {
+                assert!((y[0] - x.sin()).abs() < error, "{} at {}", method.name(), x);
+            }
+        }
+    }
+}
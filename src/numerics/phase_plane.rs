@@ -0,0 +1,173 @@
+/// Kind of a fixed point of a planar system, given by the eigenvalues of its Jacobian.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixedPointKind {
+    Saddle,
+    StableNode,
+    UnstableNode,
+    StableSpiral,
+    UnstableSpiral,
+    /// Purely imaginary eigenvalues, where the linearization does not decide stability.
+    Center,
+    /// Some eigenvalue is zero.
+    Degenerate,
+}
+
+impl FixedPointKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            FixedPointKind::Saddle => "saddle",
+            FixedPointKind::StableNode => "stable node",
+            FixedPointKind::UnstableNode => "unstable node",
+            FixedPointKind::StableSpiral => "stable spiral",
+            FixedPointKind::UnstableSpiral => "unstable spiral",
+            FixedPointKind::Center => "center",
+            FixedPointKind::Degenerate => "degenerate",
+        }
+    }
+}
+
+/// Rows of the Jacobian matrix of a planar system at a point.
+pub type Jacobian = [[f64; 2]; 2];
+
+/// Jacobian of `f` at `(x, y)`, by central differences.
+pub fn jacobian<F>(f: &F, (x, y): (f64, f64)) -> Jacobian
+where
+    F: Fn(f64, f64) -> (f64, f64),
+{
+    let h_x = 1e-6 * (1. + x.abs());
+    let h_y = 1e-6 * (1. + y.abs());
+    let (right, left) = (f(x + h_x, y), f(x - h_x, y));
+    let (up, down) = (f(x, y + h_y), f(x, y - h_y));
+    [
+        [
+            (right.0 - left.0) / (2. * h_x),
+            (up.0 - down.0) / (2. * h_y),
+        ],
+        [
+            (right.1 - left.1) / (2. * h_x),
+            (up.1 - down.1) / (2. * h_y),
+        ],
+    ]
+}
+
+/// Classifies a fixed point by the trace and determinant of the Jacobian there.
+///
+/// Quantities smaller than `tolerance`, relative to the size of the Jacobian,
+/// count as zero.
+pub fn classify(jacobian: &Jacobian, tolerance: f64) -> FixedPointKind {
+    let [[a, b], [c, d]] = *jacobian;
+    let scale = a.abs().max(b.abs()).max(c.abs()).max(d.abs()).max(1e-300);
+    let trace = (a + d) / scale;
+    let determinant = (a * d - b * c) / (scale * scale);
+    if determinant.abs() < tolerance {
+        FixedPointKind::Degenerate
+    } else if determinant < 0. {
+        FixedPointKind::Saddle
+    } else if trace.abs() < tolerance {
+        FixedPointKind::Center
+    } else if trace * trace >= 4. * determinant {
+        if trace < 0. {
+            FixedPointKind::StableNode
+        } else {
+            FixedPointKind::UnstableNode
+        }
+    } else if trace < 0. {
+        FixedPointKind::StableSpiral
+    } else {
+        FixedPointKind::UnstableSpiral
+    }
+}
+
+/// Zeros of `f` in the rectangle `x_range` × `y_range`, found by Newton's method
+/// from the centers of a `seeds` × `seeds` grid, with their classification.
+pub fn fixed_points<F>(
+    f: F,
+    x_range: (f64, f64),
+    y_range: (f64, f64),
+    seeds: usize,
+) -> Vec<((f64, f64), FixedPointKind)>
+where
+    F: Fn(f64, f64) -> (f64, f64),
+{
+    let size = (x_range.1 - x_range.0).max(y_range.1 - y_range.0);
+    let mut points: Vec<((f64, f64), FixedPointKind)> = vec![];
+    for i in 0..seeds {
+        let x = x_range.0 + (i as f64 + 0.5) / seeds as f64 * (x_range.1 - x_range.0);
+        for j in 0..seeds {
+            let y = y_range.0 + (j as f64 + 0.5) / seeds as f64 * (y_range.1 - y_range.0);
+            let zero = match newton(&f, (x, y), size * 1e-12) {
+                Some(zero) => zero,
+                None => continue,
+            };
+            let inside = (x_range.0..=x_range.1).contains(&zero.0)
+                && (y_range.0..=y_range.1).contains(&zero.1);
+            let known = points.iter().any(|(point, _)| {
+                (point.0 - zero.0).abs() < size * 1e-6 && (point.1 - zero.1).abs() < size * 1e-6
+            });
+            if inside && !known {
+                points.push((zero, classify(&jacobian(&f, zero), 1e-9)));
+            }
+        }
+    }
+    points
+}
+
+/// Zero of `f` found by Newton's method from `start`, if the steps get
+/// smaller than `tolerance`.
+fn newton<F>(f: &F, start: (f64, f64), tolerance: f64) -> Option<(f64, f64)>
+where
+    F: Fn(f64, f64) -> (f64, f64),
+{
+    let (mut x, mut y) = start;
+    for _ in 0..50 {
+        let (u, v) = f(x, y);
+        let [[a, b], [c, d]] = jacobian(f, (x, y));
+        let determinant = a * d - b * c;
+        if !determinant.is_normal() {
+            return None;
+        }
+        let dx = (d * u - b * v) / determinant;
+        let dy = (a * v - c * u) / determinant;
+        x -= dx;
+        y -= dy;
+        if !x.is_finite() || !y.is_finite() {
+            return None;
+        }
+        if dx.abs().max(dy.abs()) < tolerance {
+            return Some((x, y));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pendulum() {
+        // x' = y, y' = -sin(x) - y / 2 has stable spirals at even multiples of pi
+        // and saddles at odd ones.
+        let f = |x: f64, y: f64| (y, -x.sin() - y / 2.);
+        let mut points = fixed_points(f, (-4., 4.), (-2., 2.), 10);
+        points.sort_by(|a, b| a.0 .0.partial_cmp(&b.0 .0).unwrap());
+        let kinds: Vec<FixedPointKind> = points.iter().map(|(_, kind)| *kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                FixedPointKind::Saddle,
+                FixedPointKind::StableSpiral,
+                FixedPointKind::Saddle
+            ]
+        );
+        assert!((points[2].0 .0 - core::f64::consts::PI).abs() < 1e-9);
+
+        let node = |x: f64, y: f64| (2. * x, y);
+        assert_eq!(
+            fixed_points(node, (-1., 1.), (-1., 1.), 4),
+            vec![((0., 0.), FixedPointKind::UnstableNode)]
+        );
+        let center = [[0., 1.], [-1., 0.]];
+        assert_eq!(classify(&center, 1e-9), FixedPointKind::Center);
+    }
+}
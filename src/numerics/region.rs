@@ -0,0 +1,82 @@
+use super::Segment;
+
+/// Axis-aligned rectangle given by two opposite corners.
+pub type Cell = ((f64, f64), (f64, f64));
+
+/// Rectangles covering the set where `inside` holds, tested at the center of each
+/// of the `resolution` cells dividing `x_range` by `y_range`.
+///
+/// Neighbouring cells of a row are merged, so that few rectangles have to be drawn.
+pub fn cells<F>(
+    inside: F,
+    x_range: (f64, f64),
+    y_range: (f64, f64),
+    resolution: (usize, usize),
+) -> Vec<Cell>
+where
+    F: Fn(f64, f64) -> bool,
+{
+    let dx = (x_range.1 - x_range.0) / resolution.0 as f64;
+    let dy = (y_range.1 - y_range.0) / resolution.1 as f64;
+    let mut cells = Vec::new();
+    for j in 0..resolution.1 {
+        let bottom = y_range.0 + j as f64 * dy;
+        let y = bottom + dy / 2.;
+        let mut start = None;
+        for i in 0..=resolution.0 {
+            let left = x_range.0 + i as f64 * dx;
+            let holds = i < resolution.0 && inside(left + dx / 2., y);
+            match (start, holds) {
+                (None, true) => start = Some(left),
+                (Some(run), false) => {
+                    cells.push(((run, bottom), (left, bottom + dy)));
+                    start = None;
+                }
+                _ => (),
+            }
+        }
+    }
+    cells
+}
+
+/// Segments that separate points where `inside` holds from points where it does not,
+/// tested at distance `offset` on each side of their midpoint.
+pub fn boundary<F>(segments: Vec<Segment>, inside: F, offset: f64) -> Vec<Segment>
+where
+    F: Fn(f64, f64) -> bool,
+{
+    segments
+        .into_iter()
+        .filter(|((x0, y0), (x1, y1))| {
+            let (x, y) = ((x0 + x1) / 2., (y0 + y1) / 2.);
+            let length = (x1 - x0).hypot(y1 - y0);
+            if length == 0. {
+                return false;
+            }
+            let (nx, ny) = ((y0 - y1) / length * offset, (x1 - x0) / length * offset);
+            inside(x + nx, y + ny) != inside(x - nx, y - ny)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn half_disk() {
+        let inside = |x: f64, y: f64| x * x + y * y < 1. && y > 0.;
+        let cells = cells(inside, (-2., 2.), (-2., 2.), (400, 400));
+        let area: f64 = cells
+            .iter()
+            .map(|((x0, y0), (x1, y1))| (x1 - x0) * (y1 - y0))
+            .sum();
+        assert!((area - core::f64::consts::PI / 2.).abs() < 0.01);
+
+        // Only the half of the unit circle above the x axis bounds the region.
+        let point = |angle: f64| (angle.cos(), angle.sin());
+        let circle = vec![(point(0.5), point(0.6)), (point(-0.5), point(-0.6))];
+        let segments = boundary(circle, inside, 0.01);
+        assert_eq!(segments, vec![(point(0.5), point(0.6))]);
+    }
+}
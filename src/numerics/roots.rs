@@ -0,0 +1,171 @@
+use super::derivative;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Extremum {
+    Minimum,
+    Maximum,
+}
+
+/// Zero of `f` between `a` and `b`, where `f` changes sign, by Brent's method:
+/// inverse quadratic interpolation or secant steps, falling back to bisection
+/// when they do not make enough progress.
+pub fn brent<F>(f: &F, (mut a, mut b): (f64, f64), tolerance: f64) -> Option<f64>
+where
+    F: Fn(f64) -> f64,
+{
+    let (mut fa, mut fb) = (f(a), f(b));
+    if fa == 0. {
+        return Some(a);
+    }
+    if fb == 0. {
+        return Some(b);
+    }
+    if !(fa.is_finite() && fb.is_finite()) || fa.signum() == fb.signum() {
+        return None;
+    }
+    if fa.abs() < fb.abs() {
+        core::mem::swap(&mut a, &mut b);
+        core::mem::swap(&mut fa, &mut fb);
+    }
+    let (mut c, mut fc) = (a, fa);
+    let mut d = b - a;
+    let mut bisected = true;
+    for _ in 0..100 {
+        if fb == 0. || (b - a).abs() < tolerance {
+            return Some(b);
+        }
+        let mut s = if fa != fc && fb != fc {
+            a * fb * fc / ((fa - fb) * (fa - fc))
+                + b * fa * fc / ((fb - fa) * (fb - fc))
+                + c * fa * fb / ((fc - fa) * (fc - fb))
+        } else {
+            b - fb * (b - a) / (fb - fa)
+        };
+        let between = (s - (3. * a + b) / 4.) * (s - b) < 0.;
+        let slow = if bisected {
+            (s - b).abs() >= (b - c).abs() / 2. || (b - c).abs() < tolerance
+        } else {
+            (s - b).abs() >= (c - d).abs() / 2. || (c - d).abs() < tolerance
+        };
+        bisected = !between || slow;
+        if bisected {
+            s = (a + b) / 2.;
+        }
+        let fs = f(s);
+        if !fs.is_finite() {
+            return None;
+        }
+        d = c;
+        c = b;
+        fc = fb;
+        if fa.signum() == fs.signum() {
+            a = s;
+            fa = fs;
+        } else {
+            b = s;
+            fb = fs;
+        }
+        if fa.abs() < fb.abs() {
+            core::mem::swap(&mut a, &mut b);
+            core::mem::swap(&mut fa, &mut fb);
+        }
+    }
+    Some(b)
+}
+
+/// Zeros of `f` found where its values at consecutive points of `xs` change sign.
+///
+/// Sign changes where `f` jumps, as at poles, are not zeros and are skipped, and so are
+/// runs of consecutive points where `f` vanishes, since it is zero over a whole interval.
+pub fn zeros<F>(f: &F, xs: &[f64]) -> Vec<f64>
+where
+    F: Fn(f64) -> f64,
+{
+    let values: Vec<f64> = xs.iter().map(|&x| f(x)).collect();
+    let mut zeros = vec![];
+    for i in 0..xs.len() {
+        if values[i] == 0. {
+            let isolated = (i == 0 || values[i - 1] != 0.)
+                && values.get(i + 1).map_or(true, |after| *after != 0.);
+            if isolated {
+                zeros.push(xs[i]);
+            }
+            continue;
+        }
+        if i + 1 == xs.len() || values[i].signum() == values[i + 1].signum() {
+            continue;
+        }
+        let tolerance = 1e-12 * (1. + xs[i].abs());
+        if let Some(zero) = brent(f, (xs[i], xs[i + 1]), tolerance) {
+            let scale = values[i].abs().max(values[i + 1].abs());
+            if f(zero).abs() <= 1e-6 * scale && zero != xs[i + 1] {
+                zeros.push(zero);
+            }
+        }
+    }
+    zeros
+}
+
+/// Local extrema of `f` inside `xs`, bracketed by three consecutive points where the
+/// middle value is the largest or the smallest, and refined as zeros of the derivative.
+///
+/// Brackets where the derivative does not change sign, as around poles, are skipped.
+pub fn extrema<F>(f: &F, xs: &[f64]) -> Vec<(f64, Extremum)>
+where
+    F: Fn(f64) -> f64,
+{
+    let values: Vec<f64> = xs.iter().map(|&x| f(x)).collect();
+    let mut extrema = vec![];
+    for i in 1..xs.len().saturating_sub(1) {
+        let (before, value, after) = (values[i - 1], values[i], values[i + 1]);
+        let kind = if value > before && value >= after {
+            Extremum::Maximum
+        } else if value < before && value <= after {
+            Extremum::Minimum
+        } else {
+            continue;
+        };
+        let h = 1e-6 * (xs[i + 1] - xs[i - 1]);
+        let slope = |x: f64| derivative(f, x, 1, h);
+        let tolerance = 1e-10 * (1. + xs[i].abs());
+        if let Some(x) = brent(&slope, (xs[i - 1], xs[i + 1]), tolerance) {
+            extrema.push((x, kind));
+        }
+    }
+    extrema
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sine() {
+        let xs: Vec<f64> = itertools_num::linspace(-4., 4., 50).collect();
+        let f = |x: f64| x.sin();
+        let zeros = zeros(&f, &xs);
+        assert_eq!(zeros.len(), 3);
+        for (zero, expected) in
+            zeros
+                .iter()
+                .zip(&[-core::f64::consts::PI, 0., core::f64::consts::PI])
+        {
+            assert!((zero - expected).abs() < 1e-10);
+        }
+        let extrema = extrema(&f, &xs);
+        assert_eq!(extrema.len(), 2);
+        assert_eq!(extrema[0].1, Extremum::Minimum);
+        assert!((extrema[1].0 - core::f64::consts::FRAC_PI_2).abs() < 1e-6);
+
+        // The sign changes of the tangent at its poles are not zeros.
+        let zeros = self::zeros(&|x: f64| x.tan(), &xs);
+        assert_eq!(zeros.len(), 3);
+        // Nor are they extrema.
+        assert!(self::extrema(&|x: f64| x.tan(), &xs).is_empty());
+
+        // A function vanishing over an interval has no isolated zeros there.
+        let xs: Vec<f64> = itertools_num::linspace(-4., 4., 51).collect();
+        assert!(self::zeros(&|x: f64| x.max(0.), &xs).is_empty());
+        assert_eq!(self::zeros(&|x: f64| x.sin(), &xs).len(), 3);
+    }
+}
@@ -0,0 +1,140 @@
+use core::f64::consts::PI;
+
+/// Largest number of bins of a histogram, since a single outlier can make the
+/// Freedman-Diaconis rule ask for billions of them.
+pub const MAX_BINS: usize = 1000;
+
+/// Number of bins given by Sturges' rule, `log2(n) + 1` rounded up.
+pub fn sturges(n: usize) -> usize {
+    (n.max(1) as f64).log2().ceil() as usize + 1
+}
+
+/// Number of bins of width `2 IQR / n^(1/3)` covering the `sorted` values, as in
+/// the Freedman-Diaconis rule, falling back to Sturges' rule if the width is zero,
+/// and at most [`MAX_BINS`].
+pub fn freedman_diaconis(sorted: &[f64]) -> usize {
+    let n = sorted.len();
+    let width = 2. * (quantile(sorted, 0.75) - quantile(sorted, 0.25)) / (n as f64).cbrt();
+    let range = sorted[n - 1] - sorted[0];
+    if width > 0. {
+        ((range / width).ceil() as usize).clamp(1, MAX_BINS)
+    } else {
+        sturges(n)
+    }
+}
+
+/// Quantile of order `p` of the `sorted` values, interpolating linearly between them.
+pub fn quantile(sorted: &[f64], p: f64) -> f64 {
+    let position = p * (sorted.len() - 1) as f64;
+    let (below, fraction) = (position.floor() as usize, position.fract());
+    match sorted.get(below + 1) {
+        Some(above) => sorted[below] + fraction * (above - sorted[below]),
+        None => sorted[below],
+    }
+}
+
+/// Histogram of the `sorted` values with `bins` bins of equal width spanning them,
+/// but at most [`MAX_BINS`], as the start, the end and the height of each bin,
+/// normalized to a total area of one.
+pub fn histogram(sorted: &[f64], bins: usize) -> Vec<(f64, f64, f64)> {
+    let n = sorted.len();
+    let (low, high) = (sorted[0], sorted[n - 1]);
+    // A sample of equal values gets a bin of width one around them.
+    let (low, high) = if high > low {
+        (low, high)
+    } else {
+        (low - 0.5, high + 0.5)
+    };
+    let bins = bins.clamp(1, MAX_BINS);
+    let width = (high - low) / bins as f64;
+    let mut counts = vec![0usize; bins];
+    for value in sorted {
+        let bin = ((value - low) / width) as usize;
+        counts[bin.min(bins - 1)] += 1;
+    }
+    counts
+        .into_iter()
+        .enumerate()
+        .map(|(i, count)| {
+            let start = low + i as f64 * width;
+            (start, start + width, count as f64 / (n as f64 * width))
+        })
+        .collect()
+}
+
+/// Empirical cumulative distribution function of the `sorted` values, as the
+/// corners of its steps from `(first, 0)` to `(last, 1)`.
+pub fn ecdf(sorted: &[f64]) -> Vec<(f64, f64)> {
+    let n = sorted.len() as f64;
+    let mut corners = vec![];
+    for (i, value) in sorted.iter().enumerate() {
+        corners.push((*value, i as f64 / n));
+        corners.push((*value, (i + 1) as f64 / n));
+    }
+    corners
+}
+
+/// Bandwidth given by Silverman's rule of thumb, `0.9 min(σ, IQR / 1.34) n^(-1/5)`,
+/// for a Gaussian kernel density estimate of the `sorted` values.
+pub fn silverman_bandwidth(sorted: &[f64]) -> f64 {
+    let n = sorted.len() as f64;
+    let mean = sorted.iter().sum::<f64>() / n;
+    let deviation = (sorted.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n).sqrt();
+    let spread = (quantile(sorted, 0.75) - quantile(sorted, 0.25)) / 1.34;
+    let scale = match (deviation > 0., spread > 0.) {
+        (true, true) => deviation.min(spread),
+        (true, false) => deviation,
+        _ => 1.,
+    };
+    0.9 * scale * n.powf(-0.2)
+}
+
+/// Gaussian kernel density estimate at `x` of the distribution of `values`.
+pub fn kde(values: &[f64], bandwidth: f64, x: f64) -> f64 {
+    let normalization = values.len() as f64 * bandwidth * (2. * PI).sqrt();
+    values
+        .iter()
+        .map(|value| (-0.5 * ((x - value) / bandwidth).powi(2)).exp())
+        .sum::<f64>()
+        / normalization
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distributions() {
+        let sorted = [1., 2., 2., 3., 4., 4., 4., 8.];
+        assert_eq!(sturges(8), 4);
+        assert_eq!(quantile(&sorted, 0.5), 3.5);
+        assert_eq!(quantile(&sorted, 1.), 8.);
+        // Width 2 * (4 - 2) / 2 = 2 over a range of 7
+        assert_eq!(freedman_diaconis(&sorted), 4);
+
+        let bins = histogram(&sorted, 7);
+        assert_eq!(bins.len(), 7);
+        assert_eq!(bins[0], (1., 2., 1. / 8.));
+        assert_eq!(bins[6].2, 1. / 8.);
+        let area: f64 = bins
+            .iter()
+            .map(|(start, end, height)| (end - start) * height)
+            .sum();
+        assert!((area - 1.).abs() < 1e-12);
+
+        // A single outlier does not make the bins countless.
+        let mut outlier: Vec<f64> = (0..1000).map(|i| i as f64 / 1000.).collect();
+        outlier.push(1e9);
+        assert_eq!(freedman_diaconis(&outlier), MAX_BINS);
+        assert_eq!(histogram(&outlier, usize::MAX).len(), MAX_BINS);
+
+        let steps = ecdf(&sorted);
+        assert_eq!(steps.first(), Some(&(1., 0.)));
+        assert_eq!(steps.last(), Some(&(8., 1.)));
+
+        let bandwidth = silverman_bandwidth(&sorted);
+        let grid = itertools_num::linspace(-20., 30., 5001);
+        let area: f64 = grid.map(|x| kde(&sorted, bandwidth, x) * 0.01).sum();
+        assert!((area - 1.).abs() < 1e-6);
+    }
+}